@@ -0,0 +1,129 @@
+use crate::data_processing::CleanRecord;
+use crate::panel::Panel;
+use csv::Writer;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::error::Error;
+
+type Getter = fn(&CleanRecord) -> u32;
+
+// Count fields checked for year-over-year jumps
+const CHECKED_FIELDS: [(&str, Getter); 4] = [
+    ("prisoner_count", |r| r.prisoner_count),
+    ("state_population", |r| r.state_population),
+    ("violent_crime_total", |r| r.violent_crime_total),
+    ("property_crime_total", |r| r.property_crime_total),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnomalyConfig {
+    pub threshold: f64,      // Robust z-score of a year's log change needed to flag it
+    pub min_log_change: f64, // Ignore changes smaller than this, however unusual for the series
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        AnomalyConfig {
+            threshold: 5.0,
+            min_log_change: 0.2, // About a 20% move
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct YoyAnomaly {
+    pub jurisdiction: String,
+    pub year: u32,
+    pub field: String,
+    pub previous: u32, // Value in the prior observed year
+    pub value: u32,
+    pub log_change: f64,
+    pub robust_z: f64,
+    pub series_break: bool, // Year is flagged as a reporting change or estimate, so the jump may be real
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+// Flag implausible year-over-year changes per state series. Each log change is
+// scored against that series' median and MAD, so a state that always moves a
+// lot is not flagged for moving a lot. A one-year spike shows up as a jump and
+// an opposite jump back; only the spike year is reported.
+pub fn detect_yoy_anomalies(records: &[CleanRecord], config: AnomalyConfig) -> Vec<YoyAnomaly> {
+    let panel = Panel::new(records.to_vec());
+    let mut anomalies = Vec::new();
+
+    for (state, series) in panel.iter_series() {
+        for (field, get) in CHECKED_FIELDS {
+            let changes: Vec<(usize, f64)> = series
+                .windows(2)
+                .enumerate()
+                .filter(|(_, w)| get(&w[0]) > 0 && get(&w[1]) > 0)
+                .map(|(i, w)| (i + 1, (get(&w[1]) as f64 / get(&w[0]) as f64).ln()))
+                .collect();
+            if changes.len() < 3 {
+                continue;
+            }
+
+            let mut values: Vec<f64> = changes.iter().map(|(_, d)| *d).collect();
+            let center = median(&mut values);
+            let mut deviations: Vec<f64> = values.iter().map(|d| (d - center).abs()).collect();
+            // 1.4826 * MAD estimates the standard deviation for normal data
+            let scale = (1.4826 * median(&mut deviations)).max(1e-6);
+
+            let mut previous_flag: Option<f64> = None;
+            for &(i, change) in &changes {
+                let z = (change - center) / scale;
+                if z.abs() <= config.threshold || change.abs() < config.min_log_change {
+                    previous_flag = None;
+                    continue;
+                }
+                // Return from last year's spike
+                if previous_flag.is_some_and(|prev| prev.signum() != change.signum()) {
+                    previous_flag = None;
+                    continue;
+                }
+                previous_flag = Some(change);
+
+                let record = &series[i];
+                anomalies.push(YoyAnomaly {
+                    jurisdiction: state.to_string(),
+                    year: record.year,
+                    field: field.to_string(),
+                    previous: get(&series[i - 1]),
+                    value: get(record),
+                    log_change: change,
+                    robust_z: z,
+                    series_break: record.is_flagged(),
+                });
+            }
+        }
+    }
+
+    anomalies
+}
+
+pub fn write_anomalies_csv(anomalies: &[YoyAnomaly], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_path(output_path)?;
+    for anomaly in anomalies {
+        wtr.serialize(anomaly)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Split records into (kept, quarantined), quarantining every state-year with an anomaly
+pub fn quarantine(records: &[CleanRecord], anomalies: &[YoyAnomaly]) -> (Vec<CleanRecord>, Vec<CleanRecord>) {
+    let flagged: HashSet<(&str, u32)> = anomalies.iter().map(|a| (a.jurisdiction.as_str(), a.year)).collect();
+    records
+        .iter()
+        .cloned()
+        .partition(|r| !flagged.contains(&(r.jurisdiction.as_str(), r.year)))
+}
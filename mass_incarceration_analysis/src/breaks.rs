@@ -0,0 +1,118 @@
+use crate::data_processing::{normalize_jurisdiction, CleanRecord};
+use crate::panel::Panel;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakKind {
+    ReportingChange, // crime_reporting_change: the series is not comparable across this year
+    Estimated,       // crimes_estimated: this year's counts are FBI estimates
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeriesBreak {
+    pub year: u32,
+    pub kind: BreakKind,
+}
+
+// How regressions and tests should treat flagged years
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum BreakHandling {
+    #[default]
+    Include,
+    Exclude, // Drop every reporting-change or estimated year
+    Split,   // Start a new segment at each reporting change
+}
+
+impl CleanRecord {
+    pub fn is_flagged(&self) -> bool {
+        self.crime_reporting_change || self.crimes_estimated
+    }
+}
+
+// Flagged years in one state's series, in year order
+pub fn series_breaks(series: &[CleanRecord]) -> Vec<SeriesBreak> {
+    let mut breaks = Vec::new();
+    for record in series {
+        if record.crime_reporting_change {
+            breaks.push(SeriesBreak { year: record.year, kind: BreakKind::ReportingChange });
+        }
+        if record.crimes_estimated {
+            breaks.push(SeriesBreak { year: record.year, kind: BreakKind::Estimated });
+        }
+    }
+    breaks.sort_by_key(|b| b.year);
+    breaks
+}
+
+impl Panel {
+    pub fn breaks(&self, state: &str) -> Vec<SeriesBreak> {
+        series_breaks(self.series(state))
+    }
+}
+
+pub fn exclude_flagged(records: &[CleanRecord]) -> Vec<CleanRecord> {
+    records.iter().filter(|r| !r.is_flagged()).cloned().collect()
+}
+
+// Split each state's series at its reporting changes. The change year opens
+// the new segment, since it is the first year under the new method.
+pub fn split_at_breaks(records: &[CleanRecord]) -> Vec<Vec<CleanRecord>> {
+    let panel = Panel::new(records.to_vec());
+    let mut segments = Vec::new();
+
+    for (_, series) in panel.iter_series() {
+        let mut current: Vec<CleanRecord> = Vec::new();
+        for record in series {
+            if record.crime_reporting_change && !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+            current.push(record.clone());
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+    }
+
+    segments
+}
+
+// Record sets to analyse under the chosen handling: one set for Include and
+// Exclude, one per unbroken segment for Split
+pub fn apply_break_handling(records: &[CleanRecord], handling: BreakHandling) -> Vec<Vec<CleanRecord>> {
+    match handling {
+        BreakHandling::Include => vec![records.to_vec()],
+        BreakHandling::Exclude => vec![exclude_flagged(records)],
+        BreakHandling::Split => split_at_breaks(records),
+    }
+}
+
+// The single record set every analysis runs on under the chosen handling.
+// Split keeps each state's latest segment, the years comparable with the
+// current reporting method.
+pub fn analysis_records(records: &[CleanRecord], handling: BreakHandling) -> Vec<CleanRecord> {
+    match handling {
+        BreakHandling::Include => records.to_vec(),
+        BreakHandling::Exclude => exclude_flagged(records),
+        BreakHandling::Split => {
+            let mut latest: Vec<CleanRecord> = Vec::new();
+            for segment in split_at_breaks(records) {
+                let state = &segment[0].jurisdiction;
+                latest.retain(|r| &r.jurisdiction != state);
+                latest.extend(segment);
+            }
+            latest
+        }
+    }
+}
+
+// Break years for the records of one state, used to mark trend plots
+pub fn breaks_for_state(records: &[CleanRecord], state: &str) -> Vec<SeriesBreak> {
+    let state = normalize_jurisdiction(state);
+    let mut series: Vec<CleanRecord> = records
+        .iter()
+        .filter(|r| normalize_jurisdiction(&r.jurisdiction) == state)
+        .cloned()
+        .collect();
+    series.sort_by_key(|r| r.year);
+    series_breaks(&series)
+}
@@ -1,36 +1,22 @@
 use crate::data_processing::CleanRecord;
+use serde::Serialize;
 use std::error::Error;
 
+use crate::hypothesis::pooled_t_test;
 use statrs::distribution::{ContinuousCDF, StudentsT}; // Add ContinuousCDF to imports
 
+// Pooled-variance Student t-test: (t-statistic, two-sided p-value).
+// See the hypothesis module for Welch, paired and rank-based tests.
 pub fn perform_t_test(data1: &[f64], data2: &[f64]) -> Result<(f64, f64), &'static str> {
     if data1.is_empty() || data2.is_empty() {
         return Err("One or both datasets are empty");
     }
 
-    let mean1 = data1.iter().sum::<f64>() / data1.len() as f64;
-    let mean2 = data2.iter().sum::<f64>() / data2.len() as f64;
-
-    let var1 = data1.iter().map(|x| (x - mean1).powi(2)).sum::<f64>() / (data1.len() - 1) as f64;
-    let var2 = data2.iter().map(|x| (x - mean2).powi(2)).sum::<f64>() / (data2.len() - 1) as f64;
-
-    let pooled_variance = ((data1.len() - 1) as f64 * var1 + (data2.len() - 1) as f64 * var2)
-        / ((data1.len() + data2.len() - 2) as f64);
-
-    let t_stat = (mean1 - mean2)
-        / (pooled_variance * (1.0 / data1.len() as f64 + 1.0 / data2.len() as f64).sqrt());
-
-    let degrees_of_freedom = (data1.len() + data2.len() - 2) as f64;
-
-    let t_dist = StudentsT::new(0.0, 1.0, degrees_of_freedom).unwrap();
-
-    // Use the ContinuousCDF trait for the cdf method
-    let p_value = 2.0 * (1.0 - t_dist.cdf(t_stat.abs()));
-
-    Ok((t_stat, p_value))
+    let result = pooled_t_test(data1, data2).map_err(|_| "Each dataset needs at least two values")?;
+    Ok((result.statistic, result.p_value))
 }
-pub fn linear_regression(records: &[CleanRecord]) -> Result<(), Box<dyn Error>> {
-    // Perform linear regression calculations
+// Least-squares line of crime_rate on incarceration_rate: (slope, intercept)
+pub fn fit_line(records: &[CleanRecord]) -> (f32, f32) {
     let x: Vec<f32> = records.iter().map(|r| r.incarceration_rate).collect();
     let y: Vec<f32> = records.iter().map(|r| r.crime_rate).collect();
 
@@ -42,7 +28,108 @@ pub fn linear_regression(records: &[CleanRecord]) -> Result<(), Box<dyn Error>>
 
     let intercept = mean_y - slope * mean_x;
 
-    println!("Linear Regression: y = {:.4}x + {:.4}", slope, intercept);
+    (slope, intercept)
+}
+
+// Simple OLS fit of crime_rate on incarceration_rate with inference statistics
+#[derive(Debug, Clone, Serialize)]
+pub struct RegressionFit {
+    pub n: usize,
+    pub slope: f64,
+    pub intercept: f64,
+    pub slope_se: f64,
+    pub intercept_se: f64,
+    pub slope_t: f64,
+    pub intercept_t: f64,
+    pub slope_p: f64,
+    pub intercept_p: f64,
+    pub confidence_level: f64,
+    pub slope_ci: (f64, f64),
+    pub intercept_ci: (f64, f64),
+    pub r_squared: f64,
+    pub adj_r_squared: f64,
+    pub residual_std_error: f64,
+    pub fitted: Vec<f64>,
+    pub residuals: Vec<f64>,
+}
 
-    Ok(())
-}
\ No newline at end of file
+impl RegressionFit {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+// Standard errors, t-statistics and p-values are NaN with fewer than three
+// records, where no residual degrees of freedom are left
+pub fn linear_regression(records: &[CleanRecord]) -> Result<RegressionFit, Box<dyn Error>> {
+    if records.len() < 2 {
+        return Err("Linear regression needs at least two records".into());
+    }
+
+    let x: Vec<f64> = records.iter().map(|r| r.incarceration_rate as f64).collect();
+    let y: Vec<f64> = records.iter().map(|r| r.crime_rate as f64).collect();
+    let n = x.len() as f64;
+
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let sxx = x.iter().map(|xi| (xi - mean_x).powi(2)).sum::<f64>();
+    if sxx == 0.0 {
+        return Err("incarceration_rate has no variation".into());
+    }
+    let sxy = x.iter().zip(&y).map(|(xi, yi)| (xi - mean_x) * (yi - mean_y)).sum::<f64>();
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+
+    let fitted: Vec<f64> = x.iter().map(|xi| intercept + slope * xi).collect();
+    let residuals: Vec<f64> = y.iter().zip(&fitted).map(|(yi, fi)| yi - fi).collect();
+    let ss_res = residuals.iter().map(|e| e * e).sum::<f64>();
+    let ss_tot = y.iter().map(|yi| (yi - mean_y).powi(2)).sum::<f64>();
+
+    let df = n - 2.0;
+    let r_squared = if ss_tot > 0.0 { 1.0 - ss_res / ss_tot } else { 1.0 };
+    let adj_r_squared = if df > 0.0 { 1.0 - (1.0 - r_squared) * (n - 1.0) / df } else { f64::NAN };
+    let residual_std_error = if df > 0.0 { (ss_res / df).sqrt() } else { f64::NAN };
+
+    let slope_se = residual_std_error / sxx.sqrt();
+    let intercept_se = residual_std_error * (1.0 / n + mean_x * mean_x / sxx).sqrt();
+    let slope_t = slope / slope_se;
+    let intercept_t = intercept / intercept_se;
+
+    let confidence_level = 0.95;
+    let (slope_p, intercept_p, t_crit) = match StudentsT::new(0.0, 1.0, df) {
+        Ok(t_dist) if df > 0.0 => (
+            2.0 * (1.0 - t_dist.cdf(slope_t.abs())),
+            2.0 * (1.0 - t_dist.cdf(intercept_t.abs())),
+            t_dist.inverse_cdf(1.0 - (1.0 - confidence_level) / 2.0),
+        ),
+        _ => (f64::NAN, f64::NAN, f64::NAN),
+    };
+
+    let fit = RegressionFit {
+        n: records.len(),
+        slope,
+        intercept,
+        slope_se,
+        intercept_se,
+        slope_t,
+        intercept_t,
+        slope_p,
+        intercept_p,
+        confidence_level,
+        slope_ci: (slope - t_crit * slope_se, slope + t_crit * slope_se),
+        intercept_ci: (intercept - t_crit * intercept_se, intercept + t_crit * intercept_se),
+        r_squared,
+        adj_r_squared,
+        residual_std_error,
+        fitted,
+        residuals,
+    };
+
+    println!(
+        "Linear Regression: y = {:.4}x + {:.4} (slope SE {:.4}, p = {:.4}, R² = {:.4}, n = {})",
+        fit.slope, fit.intercept, fit.slope_se, fit.slope_p, fit.r_squared, fit.n
+    );
+
+    Ok(fit)
+}
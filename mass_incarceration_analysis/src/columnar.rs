@@ -0,0 +1,70 @@
+use crate::data_processing::{normalize_jurisdiction, CleanRecord};
+use crate::polars_pipeline::{frame_to_records, records_to_frame};
+use crate::validation::{Severity, ValidationIssue};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+
+// The cleaned panel as a frame: counts, rates, flags and covariates from
+// records_to_frame, plus the fields that had validation warnings for each
+// state-year, from the records themselves and from `issues`
+pub fn panel_frame(records: &[CleanRecord], issues: &[ValidationIssue]) -> PolarsResult<DataFrame> {
+    let mut warnings: HashMap<(String, u32), Vec<&str>> = HashMap::new();
+    for issue in issues.iter().filter(|i| i.severity == Severity::Warning) {
+        if let Ok(year) = issue.year.trim().parse::<u32>() {
+            warnings
+                .entry((normalize_jurisdiction(&issue.jurisdiction), year))
+                .or_default()
+                .push(&issue.field);
+        }
+    }
+
+    let flagged: Vec<String> = records
+        .iter()
+        .map(|r| {
+            let mut fields: Vec<&str> = r.validation_warnings.iter().map(String::as_str).collect();
+            for field in warnings.get(&(r.jurisdiction.clone(), r.year)).into_iter().flatten() {
+                if !fields.contains(field) {
+                    fields.push(field);
+                }
+            }
+            fields.join(";")
+        })
+        .collect();
+
+    let mut df = records_to_frame(records)?;
+    df.with_column(Series::new("validation_warnings", flagged))?;
+    Ok(df)
+}
+
+pub fn write_parquet(records: &[CleanRecord], issues: &[ValidationIssue], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut df = panel_frame(records, issues)?;
+    ParquetWriter::new(File::create(output_path)?).finish(&mut df)?;
+    Ok(())
+}
+
+pub fn write_ipc(records: &[CleanRecord], issues: &[ValidationIssue], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut df = panel_frame(records, issues)?;
+    IpcWriter::new(File::create(output_path)?).finish(&mut df)?;
+    Ok(())
+}
+
+pub fn read_parquet_frame(file_path: &str) -> Result<DataFrame, Box<dyn Error>> {
+    Ok(ParquetReader::new(File::open(file_path)?).finish()?)
+}
+
+pub fn read_ipc_frame(file_path: &str) -> Result<DataFrame, Box<dyn Error>> {
+    Ok(IpcReader::new(File::open(file_path)?).finish()?)
+}
+
+// Records back from a file written by write_parquet, warnings and covariates
+// included; rates are recomputed from the counts under the rate spec stored
+// with each row
+pub fn read_parquet(file_path: &str) -> Result<Vec<CleanRecord>, Box<dyn Error>> {
+    Ok(frame_to_records(&read_parquet_frame(file_path)?)?)
+}
+
+pub fn read_ipc(file_path: &str) -> Result<Vec<CleanRecord>, Box<dyn Error>> {
+    Ok(frame_to_records(&read_ipc_frame(file_path)?)?)
+}
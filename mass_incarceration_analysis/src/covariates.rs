@@ -0,0 +1,169 @@
+use crate::data_processing::CleanRecord;
+use crate::sources::strip_bom;
+use crate::states::resolve_jurisdiction;
+use csv::ReaderBuilder;
+use petgraph::graph::UnGraph;
+use std::collections::HashMap;
+use std::error::Error;
+
+// Column names accepted for the state column of a covariate file
+const STATE_COLUMNS: [&str; 4] = ["jurisdiction", "state", "state_name", "name"];
+
+// External state-year values, e.g. unemployment or median age, keyed by
+// canonical state name and year
+#[derive(Debug, Clone, Default)]
+pub struct CovariateTable {
+    pub names: Vec<String>,
+    pub values: HashMap<(String, u32), Vec<Option<f64>>>, // One entry per name
+}
+
+impl CovariateTable {
+    pub fn get(&self, state: &str, year: u32, name: &str) -> Option<f64> {
+        let column = self.names.iter().position(|n| n == name)?;
+        let state = resolve_jurisdiction(state)?;
+        self.values.get(&(state, year))?.get(column).copied().flatten()
+    }
+}
+
+// Load a CSV with a state column (jurisdiction, state, state_name or name), a
+// year column and any number of numeric covariate columns. States go through
+// the same registry as the crime data; blank or NA cells are missing values.
+pub fn load_covariates(file_path: &str) -> Result<CovariateTable, Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_path(file_path)?;
+
+    let headers: Vec<String> = rdr.headers()?.iter().map(|h| strip_bom(h).trim().to_string()).collect();
+    let find = |candidates: &[&str]| headers.iter().position(|h| candidates.contains(&h.to_lowercase().as_str()));
+    let state_col = find(&STATE_COLUMNS).ok_or_else(|| format!("No state column in {}", file_path))?;
+    let year_col = find(&["year"]).ok_or_else(|| format!("No year column in {}", file_path))?;
+    let covariate_cols: Vec<usize> = (0..headers.len())
+        .filter(|&i| i != state_col && i != year_col && !headers[i].is_empty())
+        .collect();
+
+    let mut table = CovariateTable {
+        names: covariate_cols.iter().map(|&i| headers[i].clone()).collect(),
+        values: HashMap::new(),
+    };
+
+    for result in rdr.records() {
+        let row = result?;
+        let field = |i: usize| row.get(i).unwrap_or("").trim();
+
+        let raw_name = field(state_col);
+        if raw_name.is_empty() {
+            continue; // Blank padding row
+        }
+        let state = resolve_jurisdiction(raw_name)
+            .ok_or_else(|| format!("Unrecognized jurisdiction '{}' in {}", raw_name, file_path))?;
+        let year = field(year_col)
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid year '{}' for {}", field(year_col), state))?;
+
+        let mut values = Vec::with_capacity(covariate_cols.len());
+        for &i in &covariate_cols {
+            let raw = field(i).replace(",", "");
+            if raw.is_empty() || raw.eq_ignore_ascii_case("na") || raw == "." {
+                values.push(None);
+                continue;
+            }
+            let value = raw
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid {} '{}' for {} {}", headers[i], raw, state, year))?;
+            values.push(Some(value));
+        }
+        table.values.insert((state, year), values);
+    }
+
+    Ok(table)
+}
+
+impl CleanRecord {
+    pub fn covariate(&self, name: &str) -> Option<f64> {
+        self.covariates.get(name).copied()
+    }
+
+    // A rate on the record or an attached covariate, by name
+    pub fn feature(&self, name: &str) -> Option<f64> {
+        match name {
+            "incarceration_rate" => Some(self.incarceration_rate as f64),
+            "crime_rate" => Some(self.crime_rate as f64),
+            _ => self.covariate(name),
+        }
+    }
+}
+
+// Copy every covariate for the record's state-year onto it; records without a
+// matching row are returned unchanged
+pub fn attach_covariates(records: &[CleanRecord], table: &CovariateTable) -> Vec<CleanRecord> {
+    records
+        .iter()
+        .map(|r| {
+            let mut c = r.clone();
+            let key = (resolve_jurisdiction(&r.jurisdiction).unwrap_or_else(|| r.jurisdiction.clone()), r.year);
+            if let Some(values) = table.values.get(&key) {
+                for (name, value) in table.names.iter().zip(values) {
+                    if let Some(v) = value {
+                        c.covariates.insert(name.clone(), *v);
+                    }
+                }
+            }
+            c
+        })
+        .collect()
+}
+
+// Copies of the records with crime_rate overwritten by a covariate, like
+// with_crime_measure does for offenses, so linear_regression and the graphs run
+// on it; the original crime_rate is not kept. Records without it are left out.
+pub fn with_covariate(records: &[CleanRecord], name: &str) -> Vec<CleanRecord> {
+    records
+        .iter()
+        .filter_map(|r| {
+            r.covariate(name).map(|value| {
+                let mut c = r.clone();
+                c.crime_rate = value as f32;
+                c
+            })
+        })
+        .collect()
+}
+
+// Similarity graph on any mix of rates and covariates. Features are z-scored
+// across the records, and similarity is 1 / (1 + RMS distance), so identical
+// records score 1.0. Records missing a feature get no node.
+pub fn construct_feature_similarity_graph(records: &[CleanRecord], features: &[&str], threshold: f32) -> UnGraph<String, f32> {
+    let rows: Vec<(&CleanRecord, Vec<f64>)> = records
+        .iter()
+        .filter_map(|r| {
+            let row: Option<Vec<f64>> = features.iter().map(|f| r.feature(f)).collect();
+            row.map(|row| (r, row))
+        })
+        .collect();
+
+    let n = rows.len().max(1) as f64;
+    let stats: Vec<(f64, f64)> = (0..features.len())
+        .map(|k| {
+            let mean = rows.iter().map(|(_, row)| row[k]).sum::<f64>() / n;
+            let std = (rows.iter().map(|(_, row)| (row[k] - mean).powi(2)).sum::<f64>() / n).sqrt();
+            (mean, if std > 0.0 { std } else { 1.0 })
+        })
+        .collect();
+    let z: Vec<Vec<f64>> = rows
+        .iter()
+        .map(|(_, row)| row.iter().zip(&stats).map(|(v, (mean, std))| (v - mean) / std).collect())
+        .collect();
+
+    let mut graph = UnGraph::new_undirected();
+    let nodes: Vec<_> = rows.iter().map(|(r, _)| graph.add_node(r.jurisdiction.clone())).collect();
+
+    for i in 0..rows.len() {
+        for j in (i + 1)..rows.len() {
+            let distance = (z[i].iter().zip(&z[j]).map(|(a, b)| (a - b).powi(2)).sum::<f64>() / features.len().max(1) as f64).sqrt();
+            let sim = (1.0 / (1.0 + distance)) as f32;
+            if sim > threshold {
+                graph.add_edge(nodes[i], nodes[j], sim);
+            }
+        }
+    }
+
+    graph
+}
@@ -1,97 +1,305 @@
 use csv::Reader;
-use serde::Deserialize;
+use crate::rates::RateSpec;
+use crate::sources::parse_flag;
+use crate::states::{lookup_state, resolve_jurisdiction, FEDERAL};
+use crate::validation::{RecordChecker, Severity, ValidationIssue};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
-#[derive(Debug, Deserialize,Clone)]
+// Field order mirrors the column order of crime_and_incarceration_by_state.csv.
+// Columns other than jurisdiction, year and the three required counts may be
+// absent; they read as blank cells, so check_record warns where a blank would.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "lowercase")]
-
 pub struct DirtyRecord {
     pub jurisdiction: String,
+    #[serde(default)]
+    pub includes_jails: String,
     pub year: String,
     pub prisoner_count: String,
+    #[serde(default)]
+    pub crime_reporting_change: String,
+    #[serde(default)]
+    pub crimes_estimated: String,
     pub state_population: String,
     pub violent_crime_total: String,
+    #[serde(default)]
+    pub murder_manslaughter: String,
+    #[serde(default)]
+    pub rape_legacy: String,
+    #[serde(default)]
+    pub rape_revised: String,
+    #[serde(default)]
+    pub robbery: String,
+    #[serde(default)]
+    pub agg_assault: String,
+    #[serde(default)]
+    pub property_crime_total: String,
+    #[serde(default)]
+    pub burglary: String,
+    #[serde(default)]
+    pub larceny: String,
+    #[serde(default)]
+    pub vehicle_theft: String,
 }
 
-#[derive(Debug, Default,Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct CleanRecord {
     pub jurisdiction: String,
     pub year: u32,
     pub prisoner_count: u32,
     pub state_population: u32,
     pub violent_crime_total: u32,
+    pub murder_manslaughter: u32,
+    pub rape_legacy: Option<u32>,  // Reported through the legacy definition only
+    pub rape_revised: Option<u32>, // Reported from the 2013 definition change onwards
+    pub robbery: u32,
+    pub agg_assault: u32,
+    pub property_crime_total: u32,
+    pub burglary: u32,
+    pub larceny: u32,
+    pub vehicle_theft: u32,
+    pub includes_jails: bool,         // prisoner_count includes the jail population
+    pub crime_reporting_change: bool, // State changed how it reports crime this year
+    pub crimes_estimated: bool,       // Crime counts were estimated by the FBI
     pub incarceration_rate: f32,
-    pub crime_rate: f32,
+    pub crime_rate: f32, // Violent crime per 100k (see rate_spec)
+    pub murder_manslaughter_rate: f32,
+    pub rape_legacy_rate: Option<f32>,
+    pub rape_revised_rate: Option<f32>,
+    pub robbery_rate: f32,
+    pub agg_assault_rate: f32,
+    pub property_crime_rate: f32,
+    pub burglary_rate: f32,
+    pub larceny_rate: f32,
+    pub vehicle_theft_rate: f32,
+    pub imputed: Vec<String>, // Names of count fields filled in by the imputation module
+    pub covariates: BTreeMap<String, f64>, // External state-year values attached by the covariates module
+    pub rate_spec: RateSpec,               // Scale and denominator of every rate above
+    pub validation_warnings: Vec<String>,  // Fields whose value was coerced by a validation warning
+}
+
+// Every offense column carried on a CleanRecord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Offense {
+    ViolentTotal,
+    MurderManslaughter,
+    RapeLegacy,
+    RapeRevised,
+    Robbery,
+    AggAssault,
+    PropertyTotal,
+    Burglary,
+    Larceny,
+    VehicleTheft,
 }
-fn parse_float_to_u32(field: &str) -> u32 {
-    if field.trim().is_empty() {
-        return 0; // Return a default value for empty strings
+
+impl Offense {
+    pub const ALL: [Offense; 10] = [
+        Offense::ViolentTotal,
+        Offense::MurderManslaughter,
+        Offense::RapeLegacy,
+        Offense::RapeRevised,
+        Offense::Robbery,
+        Offense::AggAssault,
+        Offense::PropertyTotal,
+        Offense::Burglary,
+        Offense::Larceny,
+        Offense::VehicleTheft,
+    ];
+
+    // Column name used in crime_and_incarceration_by_state.csv
+    pub fn column(&self) -> &'static str {
+        match self {
+            Offense::ViolentTotal => "violent_crime_total",
+            Offense::MurderManslaughter => "murder_manslaughter",
+            Offense::RapeLegacy => "rape_legacy",
+            Offense::RapeRevised => "rape_revised",
+            Offense::Robbery => "robbery",
+            Offense::AggAssault => "agg_assault",
+            Offense::PropertyTotal => "property_crime_total",
+            Offense::Burglary => "burglary",
+            Offense::Larceny => "larceny",
+            Offense::VehicleTheft => "vehicle_theft",
+        }
     }
 
-    field
-        .trim()                           // Remove any leading/trailing spaces
-        .parse::<f64>()                   // Parse the field as a floating-point number
-        .unwrap_or(0.0)                   // Default to 0.0 if parsing fails
-        .round() as u32                   // Round the float and convert to u32
+    // CleanRecord field holding the offense's rate
+    pub fn rate_field(&self) -> &'static str {
+        match self {
+            Offense::ViolentTotal => "crime_rate",
+            Offense::MurderManslaughter => "murder_manslaughter_rate",
+            Offense::RapeLegacy => "rape_legacy_rate",
+            Offense::RapeRevised => "rape_revised_rate",
+            Offense::Robbery => "robbery_rate",
+            Offense::AggAssault => "agg_assault_rate",
+            Offense::PropertyTotal => "property_crime_rate",
+            Offense::Burglary => "burglary_rate",
+            Offense::Larceny => "larceny_rate",
+            Offense::VehicleTheft => "vehicle_theft_rate",
+        }
+    }
 }
 
-pub fn clean_record(r: DirtyRecord) -> Option<CleanRecord> {
-    if r.state_population.trim().is_empty() || r.violent_crime_total.trim().is_empty() {
-        eprintln!(
-            "Missing or invalid data: State = {}, Year = {}",
-            r.jurisdiction, r.year
-        );
-        return None; // Skip records with missing data
+impl CleanRecord {
+    // Recalculate every rate from the counts under the record's RateSpec
+    // (per 100k residents unless apply_rate_spec changed it)
+    pub fn compute_rates(&mut self) {
+        self.incarceration_rate = self.rate_of(self.prisoner_count);
+        self.crime_rate = self.rate_of(self.violent_crime_total);
+        self.murder_manslaughter_rate = self.rate_of(self.murder_manslaughter);
+        self.rape_legacy_rate = self.rape_legacy.map(|n| self.rate_of(n));
+        self.rape_revised_rate = self.rape_revised.map(|n| self.rate_of(n));
+        self.robbery_rate = self.rate_of(self.robbery);
+        self.agg_assault_rate = self.rate_of(self.agg_assault);
+        self.property_crime_rate = self.rate_of(self.property_crime_total);
+        self.burglary_rate = self.rate_of(self.burglary);
+        self.larceny_rate = self.rate_of(self.larceny);
+        self.vehicle_theft_rate = self.rate_of(self.vehicle_theft);
     }
 
-    let mut c = CleanRecord::default();
-    c.jurisdiction = r.jurisdiction.clone();
-    c.year = r.year.trim_matches('"').parse::<u32>().unwrap_or_else(|_| {
-        eprintln!("Invalid year format: {}", r.year);
-        0
-    });
-    c.prisoner_count = r.prisoner_count.replace(",", "").parse::<u32>().unwrap_or_else(|_| {
-        eprintln!("Invalid prisoner_count: {}", r.prisoner_count);
-        0
-    });
-    c.state_population = parse_float_to_u32(&r.state_population);
-    c.violent_crime_total = parse_float_to_u32(&r.violent_crime_total);
-
-    if c.state_population == 0 || c.violent_crime_total == 0 {
-        eprintln!(
-            "Parsed invalid data: State = {}, Year = {}, State Population = {}, Violent Crime Total = {}",
-            r.jurisdiction, c.year, c.state_population, c.violent_crime_total
-        );
-        return None;
+    pub fn offense_count(&self, offense: Offense) -> Option<u32> {
+        match offense {
+            Offense::ViolentTotal => Some(self.violent_crime_total),
+            Offense::MurderManslaughter => Some(self.murder_manslaughter),
+            Offense::RapeLegacy => self.rape_legacy,
+            Offense::RapeRevised => self.rape_revised,
+            Offense::Robbery => Some(self.robbery),
+            Offense::AggAssault => Some(self.agg_assault),
+            Offense::PropertyTotal => Some(self.property_crime_total),
+            Offense::Burglary => Some(self.burglary),
+            Offense::Larceny => Some(self.larceny),
+            Offense::VehicleTheft => Some(self.vehicle_theft),
+        }
     }
 
-    // Calculate rates
-    c.incarceration_rate = c.prisoner_count as f32 / c.state_population as f32 * 100_000.0;
-    c.crime_rate = c.violent_crime_total as f32 / c.state_population as f32 * 100_000.0;
+    pub fn offense_rate(&self, offense: Offense) -> Option<f32> {
+        match offense {
+            Offense::ViolentTotal => Some(self.crime_rate),
+            Offense::MurderManslaughter => Some(self.murder_manslaughter_rate),
+            Offense::RapeLegacy => self.rape_legacy_rate,
+            Offense::RapeRevised => self.rape_revised_rate,
+            Offense::Robbery => Some(self.robbery_rate),
+            Offense::AggAssault => Some(self.agg_assault_rate),
+            Offense::PropertyTotal => Some(self.property_crime_rate),
+            Offense::Burglary => Some(self.burglary_rate),
+            Offense::Larceny => Some(self.larceny_rate),
+            Offense::VehicleTheft => Some(self.vehicle_theft_rate),
+        }
+    }
+}
+// Counts such as "149,852" carry thousands separators (and sometimes quotes)
+pub(crate) fn parse_thousands(field: &str) -> Option<u32> {
+    field.trim().trim_matches('"').replace(",", "").parse::<u32>().ok()
+}
 
-    Some(c)
+// Canonical form used to match jurisdictions across sources ("Alaska", " ALASKA ", "AZ").
+// Names the state registry knows map to its canonical name; anything else is
+// only trimmed and upper-cased.
+pub fn normalize_jurisdiction(name: &str) -> String {
+    if let Some(state) = lookup_state(name) {
+        return state.name.to_string();
+    }
+    name.trim_start_matches('\u{feff}')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
 }
-pub fn process_dataset(file_path: &str) -> Result<(Vec<CleanRecord>, Vec<DirtyRecord>), Box<dyn Error>> {
+
+// Parse and check one row. The record is always built (bad values coerced to 0)
+// and is only usable when none of the returned issues is an Error.
+fn check_record(r: &DirtyRecord) -> (CleanRecord, Vec<ValidationIssue>) {
+    use Severity::{Error, Warning};
+
+    let mut check = RecordChecker::new(r);
+    let mut c = CleanRecord {
+        jurisdiction: check.jurisdiction(&r.jurisdiction),
+        year: check.year(&r.year).unwrap_or(0),
+        prisoner_count: check.count("prisoner_count", &r.prisoner_count, Some(Error), Some(Warning)).unwrap_or(0),
+        state_population: check.count("state_population", &r.state_population, Some(Error), Some(Error)).unwrap_or(0),
+        violent_crime_total: check.count("violent_crime_total", &r.violent_crime_total, Some(Error), Some(Error)).unwrap_or(0),
+        murder_manslaughter: check.count("murder_manslaughter", &r.murder_manslaughter, Some(Warning), None).unwrap_or(0),
+        // Each rape column is blank on the other side of the 2013 definition change
+        rape_legacy: check.count("rape_legacy", &r.rape_legacy, None, None),
+        rape_revised: check.count("rape_revised", &r.rape_revised, None, None),
+        robbery: check.count("robbery", &r.robbery, Some(Warning), None).unwrap_or(0),
+        agg_assault: check.count("agg_assault", &r.agg_assault, Some(Warning), None).unwrap_or(0),
+        property_crime_total: check.count("property_crime_total", &r.property_crime_total, Some(Warning), Some(Warning)).unwrap_or(0),
+        burglary: check.count("burglary", &r.burglary, Some(Warning), None).unwrap_or(0),
+        larceny: check.count("larceny", &r.larceny, Some(Warning), None).unwrap_or(0),
+        vehicle_theft: check.count("vehicle_theft", &r.vehicle_theft, Some(Warning), None).unwrap_or(0),
+        includes_jails: parse_flag(&r.includes_jails),
+        crime_reporting_change: parse_flag(&r.crime_reporting_change),
+        crimes_estimated: parse_flag(&r.crimes_estimated),
+        ..Default::default()
+    };
+
+    if check.has_errors() {
+        return (c, check.issues);
+    }
+
+    c.validation_warnings = check.issues.iter().filter(|i| i.severity == Warning).map(|i| i.field.clone()).collect();
+    c.compute_rates();
+
+    (c, check.issues)
+}
+
+// Every issue found in a row, warnings included
+pub fn validate_record(r: &DirtyRecord) -> Vec<ValidationIssue> {
+    check_record(r).1
+}
+
+// Err carries the issues (at least one Error) that rejected the row
+pub fn clean_record(r: DirtyRecord) -> Result<CleanRecord, Vec<ValidationIssue>> {
+    let (c, issues) = check_record(&r);
+    if issues.iter().any(|i| i.severity == Severity::Error) {
+        Err(issues)
+    } else {
+        Ok(c)
+    }
+}
+
+pub fn is_federal(jurisdiction: &str) -> bool {
+    normalize_jurisdiction(jurisdiction) == FEDERAL
+}
+
+// Clean records, rejected rows, and every validation issue (including
+// warnings on rows that were kept)
+pub type ProcessedDataset = (Vec<CleanRecord>, Vec<DirtyRecord>, Vec<ValidationIssue>);
+
+pub fn process_dataset(file_path: &str) -> Result<ProcessedDataset, Box<dyn Error>> {
     let mut rdr = Reader::from_path(file_path)?;
     let mut clean_records = Vec::new();
     let mut invalid_records = Vec::new();
+    let mut issues = Vec::new();
 
     for result in rdr.deserialize() {
         let dirty: DirtyRecord = result?;
-        match clean_record(dirty.clone()) {
-            Some(clean) => clean_records.push(clean),
-            None => invalid_records.push(dirty),
+        if is_federal(&dirty.jurisdiction) {
+            continue; // Federal prisoners are their own series, see federal::load_federal_series
         }
+        let (clean, record_issues) = check_record(&dirty);
+        if record_issues.iter().any(|i| i.severity == Severity::Error) {
+            invalid_records.push(dirty);
+        } else {
+            clean_records.push(clean);
+        }
+        issues.extend(record_issues);
     }
 
-    Ok((clean_records, invalid_records))
+    Ok((clean_records, invalid_records, issues))
 }
 
-pub fn filter_by_state(records: &[CleanRecord], state: &str) -> Vec<CleanRecord> {
-    records
+// Records of one state under any spelling the state registry knows. An
+// unknown name is an Err; a known state without rows is an empty Vec.
+pub fn filter_by_state(records: &[CleanRecord], state: &str) -> Result<Vec<CleanRecord>, String> {
+    let resolved = resolve_jurisdiction(state).ok_or_else(|| format!("Unknown state: {}", state))?;
+    Ok(records
         .iter()
-        .filter(|r| r.jurisdiction.to_lowercase() == state.to_lowercase())
+        .filter(|r| normalize_jurisdiction(&r.jurisdiction) == resolved)
         .cloned()
-        .collect()
+        .collect())
 }
 pub fn identify_outliers(records: &[CleanRecord]) -> Vec<CleanRecord> {
     let mean_incarceration = records.iter().map(|r| r.incarceration_rate).sum::<f32>() / records.len() as f32;
@@ -101,4 +309,20 @@ pub fn identify_outliers(records: &[CleanRecord]) -> Vec<CleanRecord> {
         .filter(|r| (r.incarceration_rate - mean_incarceration).abs() > 3.0 * std_incarceration) // z-score > 3
         .cloned()
         .collect()
-}
\ No newline at end of file
+}
+
+// Swap crime_rate for another offense so the existing analyses (regressions,
+// graphs, t-tests) run on that offense instead of the violent total.
+// Records that did not report the offense are left out.
+pub fn with_crime_measure(records: &[CleanRecord], offense: Offense) -> Vec<CleanRecord> {
+    records
+        .iter()
+        .filter_map(|r| {
+            r.offense_rate(offense).map(|rate| {
+                let mut c = r.clone();
+                c.crime_rate = rate;
+                c
+            })
+        })
+        .collect()
+}
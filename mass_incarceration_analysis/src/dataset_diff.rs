@@ -0,0 +1,144 @@
+use crate::data_processing::{process_dataset, CleanRecord, Offense};
+use crate::panel::Panel;
+use csv::Writer;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+// A value that differs between two releases for the same state-year
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub jurisdiction: String,
+    pub year: u32,
+    pub field: String,
+    pub old: Option<f64>,
+    pub new: Option<f64>,
+    pub diff: Option<f64>, // new - old, when both are present
+}
+
+#[derive(Debug, Default)]
+pub struct DatasetDiff {
+    pub added: Vec<(String, u32)>,        // State-years only in the new release
+    pub removed: Vec<(String, u32)>,      // State-years only in the old release
+    pub changes: Vec<FieldChange>,        // Reported counts and flags
+    pub rate_movements: Vec<FieldChange>, // Derived per-100k rates
+    pub changes_per_field: BTreeMap<String, usize>,
+}
+
+fn flag(value: bool) -> Option<f64> {
+    Some(if value { 1.0 } else { 0.0 })
+}
+
+// Reported values of a record, by combined-file column name
+fn reported_values(r: &CleanRecord) -> Vec<(&'static str, Option<f64>)> {
+    let mut values = vec![
+        ("prisoner_count", Some(r.prisoner_count as f64)),
+        ("state_population", Some(r.state_population as f64)),
+        ("includes_jails", flag(r.includes_jails)),
+        ("crime_reporting_change", flag(r.crime_reporting_change)),
+        ("crimes_estimated", flag(r.crimes_estimated)),
+    ];
+    values.extend(Offense::ALL.iter().map(|o| (o.column(), r.offense_count(*o).map(|n| n as f64))));
+    values
+}
+
+fn rate_values(r: &CleanRecord) -> Vec<(&'static str, Option<f64>)> {
+    let mut values = vec![("incarceration_rate", Some(r.incarceration_rate as f64))];
+    values.extend(Offense::ALL.iter().map(|o| (o.rate_field(), r.offense_rate(*o).map(|n| n as f64))));
+    values
+}
+
+fn compare(
+    state: &str,
+    year: u32,
+    old: Vec<(&'static str, Option<f64>)>,
+    new: Vec<(&'static str, Option<f64>)>,
+) -> Vec<FieldChange> {
+    old.into_iter()
+        .zip(new)
+        .filter(|((_, a), (_, b))| a != b)
+        .map(|((field, a), (_, b))| FieldChange {
+            jurisdiction: state.to_string(),
+            year,
+            field: field.to_string(),
+            old: a,
+            new: b,
+            diff: a.zip(b).map(|(a, b)| b - a),
+        })
+        .collect()
+}
+
+// Compare two releases state-year by state-year through the Panel index
+pub fn diff_panels(old: &Panel, new: &Panel) -> DatasetDiff {
+    let mut diff = DatasetDiff::default();
+
+    for (state, series) in old.iter_series() {
+        for record in series {
+            match new.get(state, record.year) {
+                Some(updated) => {
+                    diff.changes.extend(compare(state, record.year, reported_values(record), reported_values(updated)));
+                    diff.rate_movements.extend(compare(state, record.year, rate_values(record), rate_values(updated)));
+                }
+                None => diff.removed.push((state.to_string(), record.year)),
+            }
+        }
+    }
+
+    for (state, series) in new.iter_series() {
+        for record in series {
+            if old.get(state, record.year).is_none() {
+                diff.added.push((state.to_string(), record.year));
+            }
+        }
+    }
+
+    for change in &diff.changes {
+        *diff.changes_per_field.entry(change.field.clone()).or_insert(0) += 1;
+    }
+
+    diff
+}
+
+// Run both releases through process_dataset and diff the cleaned panels
+pub fn diff_datasets(old_path: &str, new_path: &str) -> Result<DatasetDiff, Box<dyn Error>> {
+    let (old, _, _) = process_dataset(old_path)?;
+    let (new, _, _) = process_dataset(new_path)?;
+    Ok(diff_panels(&Panel::new(old), &Panel::new(new)))
+}
+
+pub fn write_changes_csv(changes: &[FieldChange], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_path(output_path)?;
+    for change in changes {
+        wtr.serialize(change)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn print_diff_summary(diff: &DatasetDiff) {
+    println!("\n--- Dataset Diff ---");
+    println!("Added state-years: {} {:?}", diff.added.len(), diff.added);
+    println!("Removed state-years: {} {:?}", diff.removed.len(), diff.removed);
+    for (field, count) in &diff.changes_per_field {
+        println!("Field: {:<24} | Changed values: {}", field, count);
+    }
+
+    // Largest rate movement per rate
+    let mut largest: BTreeMap<&str, &FieldChange> = BTreeMap::new();
+    for movement in &diff.rate_movements {
+        let size = movement.diff.map(f64::abs).unwrap_or(0.0);
+        let entry = largest.entry(movement.field.as_str()).or_insert(movement);
+        if size > entry.diff.map(f64::abs).unwrap_or(0.0) {
+            *entry = movement;
+        }
+    }
+    for (rate, m) in largest {
+        println!(
+            "Rate: {:<26} | Largest move: {:>10.2} ({} {})",
+            rate,
+            m.diff.unwrap_or(0.0),
+            m.jurisdiction,
+            m.year
+        );
+    }
+}
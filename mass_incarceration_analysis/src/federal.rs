@@ -0,0 +1,114 @@
+use crate::data_processing::{is_federal, parse_thousands, CleanRecord, DirtyRecord};
+use csv::Reader;
+use std::collections::HashMap;
+use std::error::Error;
+
+// Federal prisoners have no population or crime data of their own, so they are
+// kept out of the state-level rates and carried as a separate yearly series
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FederalRecord {
+    pub year: u32,
+    pub prisoner_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NationalTotal {
+    pub year: u32,
+    pub state_prisoners: u64,
+    pub federal_prisoners: u64,
+    pub total_prisoners: u64,
+    pub population: u64,        // Sum of the state populations present that year
+    pub incarceration_rate: f32, // (state + federal) under the records' RateSpec
+}
+
+// Read the FEDERAL rows of the combined dataset
+pub fn load_federal_series(file_path: &str) -> Result<Vec<FederalRecord>, Box<dyn Error>> {
+    let mut rdr = Reader::from_path(file_path)?;
+    let mut series = Vec::new();
+
+    for result in rdr.deserialize() {
+        let dirty: DirtyRecord = result?;
+        if !is_federal(&dirty.jurisdiction) {
+            continue;
+        }
+        let year = dirty
+            .year
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid year '{}' for FEDERAL", dirty.year))?;
+        let prisoner_count = parse_thousands(&dirty.prisoner_count)
+            .ok_or_else(|| format!("Invalid prisoner_count '{}' for FEDERAL {}", dirty.prisoner_count, year))?;
+
+        series.push(FederalRecord { year, prisoner_count });
+    }
+
+    series.sort_by_key(|f| f.year);
+    Ok(series)
+}
+
+// State + federal prisoners per year, sorted by year. The rate uses the scale
+// and denominator of the records' RateSpec, summing each state's denominator.
+pub fn national_totals(records: &[CleanRecord], federal: &[FederalRecord]) -> Vec<NationalTotal> {
+    let spec = records.first().map(|r| r.rate_spec.clone()).unwrap_or_default();
+    // year -> (state prisoners, population, rate denominator)
+    let mut yearly: HashMap<u32, (u64, u64, f64)> = HashMap::new();
+    for record in records {
+        let entry = yearly.entry(record.year).or_insert((0, 0, 0.0));
+        entry.0 += record.prisoner_count as u64;
+        entry.1 += record.state_population as u64;
+        entry.2 += record.rate_denominator().unwrap_or(0.0) as f64;
+    }
+    let federal_by_year: HashMap<u32, u64> = federal.iter().map(|f| (f.year, f.prisoner_count as u64)).collect();
+
+    let mut years: Vec<u32> = yearly.keys().chain(federal_by_year.keys()).cloned().collect();
+    years.sort();
+    years.dedup();
+
+    years
+        .into_iter()
+        .map(|year| {
+            let (state_prisoners, population, denominator) = yearly.get(&year).cloned().unwrap_or((0, 0, 0.0));
+            let federal_prisoners = federal_by_year.get(&year).cloned().unwrap_or(0);
+            let total_prisoners = state_prisoners + federal_prisoners;
+            let incarceration_rate = if denominator > 0.0 {
+                spec.rate(total_prisoners as f64, denominator)
+            } else {
+                0.0
+            };
+            NationalTotal {
+                year,
+                state_prisoners,
+                federal_prisoners,
+                total_prisoners,
+                population,
+                incarceration_rate,
+            }
+        })
+        .collect()
+}
+
+// Sensitivity check: add each year's federal prisoners to the states in
+// proportion to their share of that year's population, then recompute rates.
+// Years without a federal count are returned unchanged.
+pub fn allocate_federal_by_population(records: &[CleanRecord], federal: &[FederalRecord]) -> Vec<CleanRecord> {
+    let mut population_by_year: HashMap<u32, u64> = HashMap::new();
+    for record in records {
+        *population_by_year.entry(record.year).or_insert(0) += record.state_population as u64;
+    }
+    let federal_by_year: HashMap<u32, u32> = federal.iter().map(|f| (f.year, f.prisoner_count)).collect();
+
+    records
+        .iter()
+        .map(|record| {
+            let mut c = record.clone();
+            if let (Some(&federal_count), Some(&population)) =
+                (federal_by_year.get(&record.year), population_by_year.get(&record.year))
+            {
+                let share = record.state_population as f64 / population as f64;
+                c.prisoner_count += (federal_count as f64 * share).round() as u32;
+                c.incarceration_rate = c.rate_of(c.prisoner_count);
+            }
+            c
+        })
+        .collect()
+}
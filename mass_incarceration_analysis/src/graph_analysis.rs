@@ -12,6 +12,9 @@ use std::collections::HashMap;
 //use std::error::Error;
 
 
+// States whose incarceration rates differ by less than this are connected
+pub const GRAPH_THRESHOLD: f32 = 50.0;
+
 pub fn construct_graph(records: &[CleanRecord]) -> Graph<String, f32> {
     
     let mut graph = Graph::<String, f32>::new();
@@ -30,7 +33,7 @@ pub fn construct_graph(records: &[CleanRecord]) -> Graph<String, f32> {
             let state2 = &records[j];
             let rate_diff = (state1.incarceration_rate - state2.incarceration_rate).abs();
 
-            if rate_diff < 50.0 {
+            if rate_diff < GRAPH_THRESHOLD {
                 let idx1 = state_indices[&state1.jurisdiction];
                 let idx2 = state_indices[&state2.jurisdiction];
                 graph.add_edge(idx1, idx2, rate_diff);
@@ -0,0 +1,106 @@
+use crate::data_processing::{normalize_jurisdiction, CleanRecord};
+use std::collections::HashMap;
+
+// The UCR switched from the legacy to the revised rape definition in 2013.
+// Both are reported for a few overlap years, which gives a per-state ratio to
+// convert one definition into the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RapeHarmonization {
+    LegacyWhereAvailable, // Legacy counts; revised-only years scaled down to the legacy definition
+    RevisedBackcast,      // Revised counts; legacy-only years scaled up by the overlap ratio
+    FlagChange,           // Reported counts as-is, with the first revised year flagged as a break
+}
+
+// revised / legacy per state, from years that report both definitions
+#[derive(Debug, Clone, Default)]
+pub struct RapeRatios {
+    pub by_state: HashMap<String, f64>,
+    pub pooled: f64, // Used for states without overlap years
+}
+
+impl RapeRatios {
+    pub fn for_state(&self, state: &str) -> f64 {
+        *self.by_state.get(&normalize_jurisdiction(state)).unwrap_or(&self.pooled)
+    }
+}
+
+pub fn overlap_ratios(records: &[CleanRecord]) -> RapeRatios {
+    let mut sums: HashMap<String, (f64, f64)> = HashMap::new(); // state -> (legacy, revised)
+    for record in records {
+        if let (Some(legacy), Some(revised)) = (record.rape_legacy, record.rape_revised) {
+            let entry = sums.entry(normalize_jurisdiction(&record.jurisdiction)).or_insert((0.0, 0.0));
+            entry.0 += legacy as f64;
+            entry.1 += revised as f64;
+        }
+    }
+
+    let total_legacy: f64 = sums.values().map(|s| s.0).sum();
+    let total_revised: f64 = sums.values().map(|s| s.1).sum();
+    let pooled = if total_legacy > 0.0 { total_revised / total_legacy } else { 1.0 };
+
+    RapeRatios {
+        by_state: sums
+            .into_iter()
+            .filter(|(_, (legacy, _))| *legacy > 0.0)
+            .map(|(state, (legacy, revised))| (state, revised / legacy))
+            .collect(),
+        pooled,
+    }
+}
+
+// One record's rape count under the chosen method
+pub fn harmonized_rape(record: &CleanRecord, method: RapeHarmonization, ratios: &RapeRatios) -> Option<u32> {
+    let ratio = ratios.for_state(&record.jurisdiction);
+    match method {
+        RapeHarmonization::LegacyWhereAvailable => record
+            .rape_legacy
+            .or_else(|| record.rape_revised.map(|n| (n as f64 / ratio).round() as u32)),
+        RapeHarmonization::RevisedBackcast => record
+            .rape_revised
+            .or_else(|| record.rape_legacy.map(|n| (n as f64 * ratio).round() as u32)),
+        RapeHarmonization::FlagChange => record.rape_revised.or(record.rape_legacy),
+    }
+}
+
+// Violent-crime counts that make up the published total
+const VIOLENT_FIELDS: [&str; 6] = ["violent_crime_total", "murder_manslaughter", "rape_legacy", "rape_revised", "robbery", "agg_assault"];
+
+// Swap the rape term of the published violent_crime_total for a consistent
+// rape series and recompute crime_rate, so every analysis that reads
+// crime_rate sees the adjusted total. Rows whose violent-crime counts were
+// coerced by a validation warning keep the published total.
+pub fn harmonize_violent_crime(records: &[CleanRecord], method: RapeHarmonization) -> Vec<CleanRecord> {
+    let ratios = overlap_ratios(records);
+
+    // First year each state reports the revised definition
+    let mut first_revised: HashMap<String, u32> = HashMap::new();
+    for record in records.iter().filter(|r| r.rape_revised.is_some()) {
+        let year = first_revised.entry(normalize_jurisdiction(&record.jurisdiction)).or_insert(record.year);
+        *year = (*year).min(record.year);
+    }
+
+    records
+        .iter()
+        .map(|record| {
+            let mut c = record.clone();
+            if method == RapeHarmonization::FlagChange
+                && first_revised.get(&normalize_jurisdiction(&record.jurisdiction)) == Some(&record.year)
+            {
+                c.crime_reporting_change = true;
+            }
+            if record.validation_warnings.iter().any(|f| VIOLENT_FIELDS.contains(&f.as_str())) {
+                return c;
+            }
+
+            // The published total counts the revised figure where one is reported
+            let (Some(published), Some(rape)) =
+                (record.rape_revised.or(record.rape_legacy), harmonized_rape(record, method, &ratios))
+            else {
+                return c; // Neither definition reported, keep the published total
+            };
+            c.violent_crime_total = (c.violent_crime_total + rape).saturating_sub(published);
+            c.crime_rate = c.rate_of(c.violent_crime_total);
+            c
+        })
+        .collect()
+}
@@ -0,0 +1,302 @@
+use crate::data_processing::CleanRecord;
+use serde::Serialize;
+use statrs::distribution::{ContinuousCDF, Normal, StudentsT};
+use std::collections::HashMap;
+use std::error::Error;
+
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TestKind {
+    PooledT,
+    WelchT,
+    PairedT,
+    MannWhitneyU,
+    WilcoxonSignedRank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EffectSizeKind {
+    CohensD,      // Mean difference over the relevant standard deviation
+    RankBiserial, // -1..1, positive when the first sample tends to be larger
+}
+
+// Every test compares the first sample against the second. `estimate` is the
+// mean difference for t-tests and the Hodges-Lehmann shift for rank tests;
+// `ci` is the confidence interval of that estimate.
+#[derive(Debug, Clone, Serialize)]
+pub struct TestResult {
+    pub test: TestKind,
+    pub n1: usize,
+    pub n2: usize,
+    pub statistic: f64,
+    pub df: Option<f64>, // None for the normal approximation of rank tests
+    pub p_value: f64,
+    pub estimate: f64,
+    pub effect_size: f64,
+    pub effect_size_kind: EffectSizeKind,
+    pub confidence_level: f64,
+    pub ci: (f64, f64),
+}
+
+fn mean(data: &[f64]) -> f64 {
+    data.iter().sum::<f64>() / data.len() as f64
+}
+
+fn variance(data: &[f64]) -> f64 {
+    let m = mean(data);
+    data.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (data.len() - 1) as f64
+}
+
+fn t_quantile(df: f64) -> Result<StudentsT, Box<dyn Error>> {
+    Ok(StudentsT::new(0.0, 1.0, df)?)
+}
+
+fn require(data: &[f64], min: usize, name: &str) -> Result<(), Box<dyn Error>> {
+    if data.len() < min {
+        return Err(format!("{} needs at least {} observations per sample", name, min).into());
+    }
+    Ok(())
+}
+
+// Ranks have no place for NaN or infinite values
+fn require_finite(data: &[f64], name: &str) -> Result<(), Box<dyn Error>> {
+    if data.iter().any(|v| !v.is_finite()) {
+        return Err(format!("{} needs finite observations", name).into());
+    }
+    Ok(())
+}
+
+fn t_result(
+    test: TestKind,
+    n1: usize,
+    n2: usize,
+    difference: f64,
+    se: f64,
+    df: f64,
+    effect_size: f64,
+) -> Result<TestResult, Box<dyn Error>> {
+    let dist = t_quantile(df)?;
+    let statistic = difference / se;
+    let t_crit = dist.inverse_cdf(1.0 - (1.0 - CONFIDENCE_LEVEL) / 2.0);
+    Ok(TestResult {
+        test,
+        n1,
+        n2,
+        statistic,
+        df: Some(df),
+        p_value: 2.0 * (1.0 - dist.cdf(statistic.abs())),
+        estimate: difference,
+        effect_size,
+        effect_size_kind: EffectSizeKind::CohensD,
+        confidence_level: CONFIDENCE_LEVEL,
+        ci: (difference - t_crit * se, difference + t_crit * se),
+    })
+}
+
+// Student's t-test assuming equal variances
+pub fn pooled_t_test(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    require(a, 2, "Pooled t-test")?;
+    require(b, 2, "Pooled t-test")?;
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let pooled_variance = ((n1 - 1.0) * variance(a) + (n2 - 1.0) * variance(b)) / (n1 + n2 - 2.0);
+    let difference = mean(a) - mean(b);
+    let se = (pooled_variance * (1.0 / n1 + 1.0 / n2)).sqrt();
+    let d = difference / pooled_variance.sqrt();
+    t_result(TestKind::PooledT, a.len(), b.len(), difference, se, n1 + n2 - 2.0, d)
+}
+
+// Welch's t-test with Welch-Satterthwaite degrees of freedom
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    require(a, 2, "Welch t-test")?;
+    require(b, 2, "Welch t-test")?;
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let (v1, v2) = (variance(a) / n1, variance(b) / n2);
+    let difference = mean(a) - mean(b);
+    let se = (v1 + v2).sqrt();
+    let df = (v1 + v2).powi(2) / (v1.powi(2) / (n1 - 1.0) + v2.powi(2) / (n2 - 1.0));
+    // Cohen's d with the average of the two variances
+    let d = difference / ((variance(a) + variance(b)) / 2.0).sqrt();
+    t_result(TestKind::WelchT, a.len(), b.len(), difference, se, df, d)
+}
+
+// t-test on the differences of paired observations (a[i] with b[i])
+pub fn paired_t_test(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    if a.len() != b.len() {
+        return Err("Paired t-test needs samples of equal length".into());
+    }
+    require(a, 2, "Paired t-test")?;
+    let diffs: Vec<f64> = a.iter().zip(b).map(|(x, y)| x - y).collect();
+    let n = diffs.len() as f64;
+    let difference = mean(&diffs);
+    let sd = variance(&diffs).sqrt();
+    t_result(TestKind::PairedT, a.len(), b.len(), difference, sd / n.sqrt(), n - 1.0, difference / sd)
+}
+
+// Average ranks (1-based) with ties sharing their mean rank, plus the
+// tie-correction term sum(t^3 - t)
+fn ranks(values: &[f64]) -> (Vec<f64>, f64) {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| values[i].total_cmp(&values[j]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut ties = 0.0;
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        for &i in &order[start..=end] {
+            ranks[i] = rank;
+        }
+        let t = (end - start + 1) as f64;
+        ties += t * t * t - t;
+        start = end + 1;
+    }
+    (ranks, ties)
+}
+
+fn normal_p_value(z: f64) -> f64 {
+    let normal = Normal::new(0.0, 1.0).unwrap();
+    2.0 * (1.0 - normal.cdf(z.abs()))
+}
+
+fn z_critical() -> f64 {
+    Normal::new(0.0, 1.0).unwrap().inverse_cdf(1.0 - (1.0 - CONFIDENCE_LEVEL) / 2.0)
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+// Order-statistic confidence interval: with k = center - z * spread from the
+// normal approximation of the statistic, the bounds are the k-th smallest and
+// k-th largest of the sorted estimates
+fn rank_ci(sorted: &[f64], center: f64, spread: f64) -> (f64, f64) {
+    let k = (center - z_critical() * spread).round().max(1.0) as usize;
+    let k = k.min(sorted.len());
+    (sorted[k - 1], sorted[sorted.len() - k])
+}
+
+// Mann-Whitney U (Wilcoxon rank-sum) with tie and continuity corrections
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    require(a, 1, "Mann-Whitney U")?;
+    require(b, 1, "Mann-Whitney U")?;
+    require_finite(a, "Mann-Whitney U")?;
+    require_finite(b, "Mann-Whitney U")?;
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+
+    let combined: Vec<f64> = a.iter().chain(b).copied().collect();
+    let (rank, ties) = ranks(&combined);
+    let rank_sum_a: f64 = rank[..a.len()].iter().sum();
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+
+    let n = n1 + n2;
+    let mean_u = n1 * n2 / 2.0;
+    let sd_u = (n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)))).sqrt();
+    let z = if sd_u > 0.0 { ((u - mean_u).abs() - 0.5).max(0.0) / sd_u * (u - mean_u).signum() } else { 0.0 };
+
+    // Hodges-Lehmann shift and its interval from the pairwise differences
+    let mut shifts: Vec<f64> = a.iter().flat_map(|x| b.iter().map(move |y| x - y)).collect();
+    shifts.sort_by(f64::total_cmp);
+    let plain_sd = (n1 * n2 * (n + 1.0) / 12.0).sqrt();
+
+    Ok(TestResult {
+        test: TestKind::MannWhitneyU,
+        n1: a.len(),
+        n2: b.len(),
+        statistic: u,
+        df: None,
+        p_value: normal_p_value(z),
+        estimate: median(&shifts),
+        effect_size: 2.0 * u / (n1 * n2) - 1.0,
+        effect_size_kind: EffectSizeKind::RankBiserial,
+        confidence_level: CONFIDENCE_LEVEL,
+        ci: rank_ci(&shifts, mean_u, plain_sd),
+    })
+}
+
+// Wilcoxon signed-rank test on paired observations. Zero differences are
+// dropped; ties and continuity are corrected for in the normal approximation.
+pub fn wilcoxon_signed_rank(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    if a.len() != b.len() {
+        return Err("Wilcoxon signed-rank needs samples of equal length".into());
+    }
+    require_finite(a, "Wilcoxon signed-rank")?;
+    require_finite(b, "Wilcoxon signed-rank")?;
+    let diffs: Vec<f64> = a.iter().zip(b).map(|(x, y)| x - y).filter(|d| *d != 0.0).collect();
+    require(&diffs, 1, "Wilcoxon signed-rank (non-zero differences)")?;
+    let n = diffs.len() as f64;
+
+    let (rank, ties) = ranks(&diffs.iter().map(|d| d.abs()).collect::<Vec<f64>>());
+    let w_plus: f64 = diffs.iter().zip(&rank).filter(|(d, _)| **d > 0.0).map(|(_, r)| r).sum();
+    let total = n * (n + 1.0) / 2.0;
+    let w_minus = total - w_plus;
+
+    let mean_w = total / 2.0;
+    let sd_w = (n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - ties / 48.0).sqrt();
+    let z = if sd_w > 0.0 { ((w_plus - mean_w).abs() - 0.5).max(0.0) / sd_w * (w_plus - mean_w).signum() } else { 0.0 };
+
+    // Pseudo-median of the differences and its interval from the Walsh averages
+    let mut walsh: Vec<f64> = Vec::new();
+    for i in 0..diffs.len() {
+        for j in i..diffs.len() {
+            walsh.push((diffs[i] + diffs[j]) / 2.0);
+        }
+    }
+    walsh.sort_by(f64::total_cmp);
+    let plain_sd = (n * (n + 1.0) * (2.0 * n + 1.0) / 24.0).sqrt();
+
+    Ok(TestResult {
+        test: TestKind::WilcoxonSignedRank,
+        n1: a.len(),
+        n2: b.len(),
+        statistic: w_plus,
+        df: None,
+        p_value: normal_p_value(z),
+        estimate: median(&walsh),
+        effect_size: (w_plus - w_minus) / total,
+        effect_size_kind: EffectSizeKind::RankBiserial,
+        confidence_level: CONFIDENCE_LEVEL,
+        ci: rank_ci(&walsh, mean_w, plain_sd),
+    })
+}
+
+// Values of two state series for the years both report, in year order, ready
+// for the paired tests
+pub fn paired_by_year(
+    a: &[CleanRecord],
+    b: &[CleanRecord],
+    value: impl Fn(&CleanRecord) -> f64,
+) -> (Vec<f64>, Vec<f64>) {
+    let b_by_year: HashMap<u32, &CleanRecord> = b.iter().map(|r| (r.year, r)).collect();
+    let mut pairs: Vec<(u32, f64, f64)> = a
+        .iter()
+        .filter_map(|r| b_by_year.get(&r.year).map(|other| (r.year, value(r), value(other))))
+        .collect();
+    pairs.sort_by_key(|p| p.0);
+    pairs.into_iter().map(|(_, x, y)| (x, y)).unzip()
+}
+
+pub fn print_test_result(label: &str, result: &TestResult) {
+    println!(
+        "{} ({:?}): statistic = {:.4}{} | p-value = {:.4} | estimate = {:.4} [{:.4}, {:.4}] | {:?} = {:.4}",
+        label,
+        result.test,
+        result.statistic,
+        result.df.map(|df| format!(", df = {:.2}", df)).unwrap_or_default(),
+        result.p_value,
+        result.estimate,
+        result.ci.0,
+        result.ci.1,
+        result.effect_size_kind,
+        result.effect_size
+    );
+}
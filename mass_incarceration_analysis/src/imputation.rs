@@ -0,0 +1,199 @@
+use crate::data_processing::{normalize_jurisdiction, parse_thousands, CleanRecord, DirtyRecord};
+use crate::panel::Panel;
+use crate::sources::parse_flag;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImputationStrategy {
+    Linear,                  // Straight line between the neighbouring observed years
+    CarryForward,            // Previous observed year (next one for a leading gap)
+    PopulationInterpolation, // Geometric population between census-anchored years, rates linear
+    ModelBased,              // Per-state linear trend in year, fitted on the observed years
+}
+
+type Getter = fn(&CleanRecord) -> u32;
+type Setter = fn(&mut CleanRecord, u32);
+
+// Count fields that imputation fills in; rates are recomputed from them
+const COUNT_FIELDS: [(&str, Getter, Setter); 10] = [
+    ("prisoner_count", |r| r.prisoner_count, |r, v| r.prisoner_count = v),
+    ("state_population", |r| r.state_population, |r, v| r.state_population = v),
+    ("violent_crime_total", |r| r.violent_crime_total, |r, v| r.violent_crime_total = v),
+    ("murder_manslaughter", |r| r.murder_manslaughter, |r, v| r.murder_manslaughter = v),
+    ("robbery", |r| r.robbery, |r, v| r.robbery = v),
+    ("agg_assault", |r| r.agg_assault, |r, v| r.agg_assault = v),
+    ("property_crime_total", |r| r.property_crime_total, |r, v| r.property_crime_total = v),
+    ("burglary", |r| r.burglary, |r, v| r.burglary = v),
+    ("larceny", |r| r.larceny, |r, v| r.larceny = v),
+    ("vehicle_theft", |r| r.vehicle_theft, |r, v| r.vehicle_theft = v),
+];
+
+impl CleanRecord {
+    pub fn is_imputed(&self) -> bool {
+        !self.imputed.is_empty()
+    }
+}
+
+pub fn exclude_imputed(records: &[CleanRecord]) -> Vec<CleanRecord> {
+    records.iter().filter(|r| !r.is_imputed()).cloned().collect()
+}
+
+fn is_census_year(year: u32) -> bool {
+    year.is_multiple_of(10)
+}
+
+fn lerp(x0: f64, y0: f64, x1: f64, y1: f64, x: f64) -> f64 {
+    if x1 == x0 {
+        return y0;
+    }
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
+// Least-squares trend of one field on year, evaluated at `year`
+fn trend(series: &[CleanRecord], value: impl Fn(&CleanRecord) -> f64, year: u32) -> Option<f64> {
+    if series.len() < 2 {
+        return None;
+    }
+    let n = series.len() as f64;
+    let mean_x = series.iter().map(|r| r.year as f64).sum::<f64>() / n;
+    let mean_y = series.iter().map(&value).sum::<f64>() / n;
+    let sxx = series.iter().map(|r| (r.year as f64 - mean_x).powi(2)).sum::<f64>();
+    let sxy = series.iter().map(|r| (r.year as f64 - mean_x) * (value(r) - mean_y)).sum::<f64>();
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    Some(mean_y + slope * (year as f64 - mean_x))
+}
+
+// Fill one missing year of a state's series. `known` holds values the source
+// did report for that year (e.g. a prisoner_count without crime data).
+fn impute_cell(
+    state: &str,
+    year: u32,
+    series: &[CleanRecord],
+    known: Option<&DirtyRecord>,
+    strategy: ImputationStrategy,
+) -> Option<CleanRecord> {
+    let prev = series.iter().rev().find(|r| r.year < year);
+    let next = series.iter().find(|r| r.year > year);
+    let nearest = prev.or(next)?;
+
+    let mut c = CleanRecord {
+        jurisdiction: state.to_string(),
+        year,
+        rate_spec: nearest.rate_spec.clone(),
+        ..Default::default()
+    };
+
+    // Population anchors: census years when the gap sits between two of them
+    let census_prev = series.iter().rev().find(|r| r.year < year && is_census_year(r.year));
+    let census_next = series.iter().find(|r| r.year > year && is_census_year(r.year));
+    let (anchor_prev, anchor_next) = match (census_prev, census_next) {
+        (Some(a), Some(b)) => (Some(a), Some(b)),
+        _ => (prev, next),
+    };
+    let population = match (anchor_prev, anchor_next) {
+        (Some(a), Some(b)) => {
+            let growth = (b.state_population as f64 / a.state_population as f64).ln();
+            let t = (year - a.year) as f64 / (b.year - a.year) as f64;
+            a.state_population as f64 * (growth * t).exp()
+        }
+        _ => nearest.state_population as f64,
+    };
+
+    for (name, get, set) in COUNT_FIELDS {
+        let value = match (strategy, prev, next) {
+            (ImputationStrategy::CarryForward, Some(p), _) => get(p) as f64,
+            (ImputationStrategy::ModelBased, _, _) => {
+                trend(series, |r| get(r) as f64, year).unwrap_or(get(nearest) as f64)
+            }
+            (ImputationStrategy::PopulationInterpolation, Some(p), Some(n)) => {
+                if name == "state_population" {
+                    population
+                } else {
+                    // Interpolate the rate, then scale by the interpolated population
+                    let rate = |r: &CleanRecord| get(r) as f64 / r.state_population as f64;
+                    lerp(p.year as f64, rate(p), n.year as f64, rate(n), year as f64) * population
+                }
+            }
+            (_, Some(p), Some(n)) => lerp(p.year as f64, get(p) as f64, n.year as f64, get(n) as f64, year as f64),
+            _ => get(nearest) as f64,
+        };
+        set(&mut c, value.max(0.0).round() as u32);
+        c.imputed.push(name.to_string());
+    }
+    c.includes_jails = nearest.includes_jails;
+
+    // A covariate rate denominator is interpolated like the counts so the
+    // imputed rates stay defined under the series' spec
+    if let Some(name) = c.rate_spec.denominator.covariate_name() {
+        let value = match (prev.and_then(|p| p.denominator_covariate()), next.and_then(|n| n.denominator_covariate())) {
+            (Some(a), Some(b)) if strategy != ImputationStrategy::CarryForward => {
+                lerp(prev?.year as f64, a, next?.year as f64, b, year as f64)
+            }
+            (Some(a), _) | (None, Some(a)) => a,
+            (None, None) => return None,
+        };
+        c.covariates.insert(name.to_string(), value);
+        c.imputed.push(name.to_string());
+    }
+
+    // Only carry a rape definition when both neighbours agree on which one applies
+    let carried = |get: fn(&CleanRecord) -> Option<u32>| match (prev.and_then(get), next.and_then(get)) {
+        (Some(a), Some(b)) => Some(((a + b) as f64 / 2.0).round() as u32),
+        (Some(a), None) if next.is_none() => Some(a),
+        (None, Some(b)) if prev.is_none() => Some(b),
+        _ => None,
+    };
+    c.rape_legacy = carried(|r| r.rape_legacy);
+    c.rape_revised = carried(|r| r.rape_revised);
+    if c.rape_legacy.is_some() {
+        c.imputed.push("rape_legacy".to_string());
+    }
+    if c.rape_revised.is_some() {
+        c.imputed.push("rape_revised".to_string());
+    }
+
+    // Values the source did report for this year win over imputed ones
+    if let Some(dirty) = known {
+        if let Some(count) = parse_thousands(&dirty.prisoner_count) {
+            c.prisoner_count = count;
+            c.imputed.retain(|f| f != "prisoner_count");
+        }
+        c.includes_jails = parse_flag(&dirty.includes_jails);
+        c.crime_reporting_change = parse_flag(&dirty.crime_reporting_change);
+        c.crimes_estimated = parse_flag(&dirty.crimes_estimated);
+    }
+
+    if c.state_population == 0 {
+        return None;
+    }
+    c.compute_rates();
+    Some(c)
+}
+
+// Observed records plus one imputed record per missing (state, year) cell of
+// the panel. `partial` are rows process_dataset rejected, whose reported values
+// are kept where present.
+pub fn impute_missing(
+    records: &[CleanRecord],
+    partial: &[DirtyRecord],
+    strategy: ImputationStrategy,
+) -> Vec<CleanRecord> {
+    let panel = Panel::new(records.to_vec());
+    let known: HashMap<(String, u32), &DirtyRecord> = partial
+        .iter()
+        .filter_map(|d| {
+            let year = d.year.trim().parse::<u32>().ok()?;
+            Some(((normalize_jurisdiction(&d.jurisdiction), year), d))
+        })
+        .collect();
+
+    let mut filled = records.to_vec();
+    for (state, year) in panel.missing_cells() {
+        let series = panel.series(&state);
+        if let Some(record) = impute_cell(&state, year, series, known.get(&(state.clone(), year)).copied(), strategy) {
+            filled.push(record);
+        }
+    }
+
+    Panel::new(filled).into_records()
+}
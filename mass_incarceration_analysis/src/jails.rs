@@ -0,0 +1,97 @@
+use crate::calculations::fit_line;
+use crate::data_processing::{identify_outliers, CleanRecord};
+use crate::graph_analysis::construct_graph;
+
+// Alaska, Connecticut, Delaware, Hawaii, Rhode Island and Vermont run unified
+// prison/jail systems, so their prisoner_count includes jail inmates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JailAdjustment {
+    Keep,       // Compare everyone as reported
+    Exclude,    // Drop the jail-including states
+    Separate,   // Analyse prison-only and jail-including states as two groups
+    Scale(f32), // Multiply jail-including counts by this prison share (e.g. 0.7)
+}
+
+// Record sets to analyse under the chosen mode (two sets for Separate)
+pub fn adjust_for_jails(records: &[CleanRecord], mode: JailAdjustment) -> Vec<Vec<CleanRecord>> {
+    let (with_jails, prison_only): (Vec<CleanRecord>, Vec<CleanRecord>) =
+        records.iter().cloned().partition(|r| r.includes_jails);
+
+    match mode {
+        JailAdjustment::Keep => vec![records.to_vec()],
+        JailAdjustment::Exclude => vec![prison_only],
+        JailAdjustment::Separate => vec![prison_only, with_jails],
+        JailAdjustment::Scale(share) => vec![records
+            .iter()
+            .map(|r| {
+                let mut c = r.clone();
+                if c.includes_jails {
+                    c.prisoner_count = (c.prisoner_count as f32 * share).round() as u32;
+                    c.incarceration_rate = c.rate_of(c.prisoner_count);
+                }
+                c
+            })
+            .collect()],
+    }
+}
+
+// Headline results of the graph, outlier and regression analyses for one record set
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisSummary {
+    pub label: String,
+    pub records: usize,
+    pub graph_nodes: usize,
+    pub graph_edges: usize,
+    pub outliers: Vec<(String, u32)>,
+    pub slope: f32,
+    pub intercept: f32,
+}
+
+pub fn summarize_analyses(label: &str, records: &[CleanRecord]) -> AnalysisSummary {
+    let graph = construct_graph(records);
+    let (slope, intercept) = fit_line(records);
+    AnalysisSummary {
+        label: label.to_string(),
+        records: records.len(),
+        graph_nodes: graph.node_count(),
+        graph_edges: graph.edge_count(),
+        outliers: identify_outliers(records).iter().map(|r| (r.jurisdiction.clone(), r.year)).collect(),
+        slope,
+        intercept,
+    }
+}
+
+// Baseline summary first, then one per adjusted record set
+pub fn jail_adjustment_report(records: &[CleanRecord], mode: JailAdjustment) -> Vec<AnalysisSummary> {
+    let mut report = vec![summarize_analyses("As reported", records)];
+    let labels: &[&str] = match mode {
+        JailAdjustment::Keep => &["Keep"],
+        JailAdjustment::Exclude => &["Excluding jail-including states"],
+        JailAdjustment::Separate => &["Prison-only states", "Jail-including states"],
+        JailAdjustment::Scale(_) => &["Jail-including states scaled"],
+    };
+    for (label, group) in labels.iter().zip(adjust_for_jails(records, mode)) {
+        report.push(summarize_analyses(label, &group));
+    }
+    report
+}
+
+pub fn print_jail_adjustment_report(report: &[AnalysisSummary]) {
+    let Some(baseline) = report.first() else {
+        return;
+    };
+    println!("\n--- includes_jails Adjustment ---");
+    for summary in report {
+        println!(
+            "{:<32} | n = {:>4} | edges = {:>6} ({:+}) | outliers = {:>2} ({:+}) | slope = {:.4} ({:+.4})",
+            summary.label,
+            summary.records,
+            summary.graph_edges,
+            summary.graph_edges as i64 - baseline.graph_edges as i64,
+            summary.outliers.len(),
+            summary.outliers.len() as i64 - baseline.outliers.len() as i64,
+            summary.slope,
+            summary.slope - baseline.slope
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::data_processing::CleanRecord;
+use crate::ols::{fit_design, hc3_label, solve, DesignMatrix, OlsFit, OlsSolver, OlsSpec};
+use crate::panel::Panel;
+use ndarray::{Array1, Array2};
+use serde::Serialize;
+use statrs::distribution::{ContinuousCDF, FisherSnedecor};
+use std::error::Error;
+
+// Lags of the predictor (0 is the same year) and how many lags of the
+// response enter as autoregressive terms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LagStructure {
+    pub predictor_lags: Vec<usize>,
+    pub response_lags: usize,
+}
+
+impl LagStructure {
+    // Same year through `max_lag` years back, plus one autoregressive term
+    pub fn up_to(max_lag: usize) -> Self {
+        LagStructure { predictor_lags: (0..=max_lag).collect(), response_lags: 1 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DistributedLagFit {
+    pub jurisdiction: Option<String>, // None for the pooled fit with state fixed effects
+    pub predictor: String,
+    pub fit: OlsFit,
+    pub long_run_effect: f64, // Sum of predictor lags / (1 - sum of response lags)
+}
+
+// Per-state fits followed by the pooled fit, and the states that could not
+// be fitted with the reason
+#[derive(Debug, Clone, Serialize)]
+pub struct DistributedLagResults {
+    pub fits: Vec<DistributedLagFit>,
+    pub skipped: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrangerTest {
+    pub cause: String,
+    pub effect: String,
+    pub jurisdiction: Option<String>, // None for the pooled test
+    pub lags: usize,
+    pub n: usize,
+    pub f_statistic: f64,
+    pub df1: usize,
+    pub df2: usize,
+    pub p_value: f64, // Small p: past `cause` helps predict `effect`
+}
+
+// Per-state tests followed by the pooled test, and the states that could not
+// be tested with the reason
+#[derive(Debug, Clone, Serialize)]
+pub struct GrangerResults {
+    pub tests: Vec<GrangerTest>,
+    pub skipped: Vec<(String, String)>,
+}
+
+// One observation with its lagged values; rows needing a year the state
+// lacks are skipped rather than bridged
+#[derive(Clone)]
+struct LaggedRow {
+    state: String,
+    year: u32,
+    response: f64,
+    predictors: Vec<f64>,
+    responses: Vec<f64>,
+}
+
+fn lagged_rows(panel: &Panel, response: &str, predictor: &str, predictor_lags: &[usize], response_lags: usize) -> Vec<LaggedRow> {
+    let value = |state: &str, year: u32, lag: usize, name: &str| {
+        let year = year.checked_sub(lag as u32)?;
+        panel.get(state, year)?.feature(name).filter(|v| v.is_finite())
+    };
+    panel
+        .iter_series()
+        .flat_map(|(state, series)| {
+            series.iter().filter_map(move |r| {
+                Some(LaggedRow {
+                    state: state.to_string(),
+                    year: r.year,
+                    response: value(state, r.year, 0, response)?,
+                    predictors: predictor_lags.iter().map(|l| value(state, r.year, *l, predictor)).collect::<Option<_>>()?,
+                    responses: (1..=response_lags).map(|l| value(state, r.year, l, response)).collect::<Option<_>>()?,
+                })
+            })
+        })
+        .collect()
+}
+
+fn lag_name(name: &str, lag: usize) -> String {
+    if lag == 0 {
+        name.to_string()
+    } else {
+        format!("{}_lag{}", name, lag)
+    }
+}
+
+// Intercept, predictor lags, response lags and, when pooling, a dummy for
+// every state but the first
+fn lag_design(
+    rows: &[LaggedRow],
+    response: &str,
+    predictor: &str,
+    predictor_lags: &[usize],
+    response_lags: usize,
+    state_effects: bool,
+) -> Result<DesignMatrix, Box<dyn Error>> {
+    let mut states: Vec<&str> = rows.iter().map(|r| r.state.as_str()).collect();
+    states.dedup();
+    let dummies = if state_effects { &states[1.min(states.len())..] } else { &[][..] };
+
+    let mut names = vec!["intercept".to_string()];
+    names.extend(predictor_lags.iter().map(|l| lag_name(predictor, *l)));
+    names.extend((1..=response_lags).map(|l| lag_name(response, l)));
+    names.extend(dummies.iter().map(|s| format!("state_{}", s)));
+
+    let k = names.len();
+    if rows.len() <= k {
+        return Err(format!("Lag model needs more complete years ({}) than columns ({})", rows.len(), k).into());
+    }
+    let mut values = Vec::with_capacity(rows.len() * k);
+    for row in rows {
+        values.push(1.0);
+        // Restricted Granger models pass no predictor lags and ignore the row's
+        values.extend(row.predictors.iter().take(predictor_lags.len()));
+        values.extend(&row.responses);
+        values.extend(dummies.iter().map(|s| if *s == row.state { 1.0 } else { 0.0 }));
+    }
+
+    Ok(DesignMatrix {
+        names,
+        x: Array2::from_shape_vec((rows.len(), k), values)?,
+        y: Array1::from_iter(rows.iter().map(|r| r.response)),
+        rows: rows.iter().map(|r| (r.state.clone(), r.year)).collect(),
+        dropped: 0,
+    })
+}
+
+fn fit_lags(
+    rows: &[LaggedRow],
+    jurisdiction: Option<&str>,
+    response: &str,
+    predictor: &str,
+    lags: &LagStructure,
+) -> Result<DistributedLagFit, Box<dyn Error>> {
+    let design = lag_design(rows, response, predictor, &lags.predictor_lags, lags.response_lags, jurisdiction.is_none())?;
+    let spec = OlsSpec::new(response, Vec::new());
+    let fit = fit_design(&design, &spec)?;
+
+    let p = lags.predictor_lags.len();
+    let predictor_sum: f64 = fit.coefficients[1..=p].iter().map(|c| c.estimate).sum();
+    let response_sum: f64 = fit.coefficients[p + 1..=p + lags.response_lags].iter().map(|c| c.estimate).sum();
+    Ok(DistributedLagFit {
+        jurisdiction: jurisdiction.map(str::to_string),
+        predictor: predictor.to_string(),
+        fit,
+        long_run_effect: predictor_sum / (1.0 - response_sum),
+    })
+}
+
+// Distributed-lag regression of `response` on lags of `predictor`, one fit
+// per state followed by a pooled fit with state fixed effects. States that
+// cannot be fitted (e.g. too few consecutive years for the lag structure) are
+// reported in `skipped`.
+pub fn distributed_lag(
+    records: &[CleanRecord],
+    response: &str,
+    predictor: &str,
+    lags: &LagStructure,
+) -> Result<DistributedLagResults, Box<dyn Error>> {
+    let panel = Panel::new(records.to_vec());
+    let rows = lagged_rows(&panel, response, predictor, &lags.predictor_lags, lags.response_lags);
+
+    let mut results = DistributedLagResults { fits: Vec::new(), skipped: Vec::new() };
+    for state in panel.states() {
+        let state_rows: Vec<LaggedRow> = rows.iter().filter(|r| r.state == *state).cloned().collect();
+        match fit_lags(&state_rows, Some(state.as_str()), response, predictor, lags) {
+            Ok(fit) => results.fits.push(fit),
+            Err(e) => results.skipped.push((state.clone(), e.to_string())),
+        }
+    }
+    results.fits.push(fit_lags(&rows, None, response, predictor, lags)?);
+    Ok(results)
+}
+
+fn residual_sum_of_squares(design: &DesignMatrix) -> Result<f64, Box<dyn Error>> {
+    let (beta, _) = solve(&design.x, &design.y, OlsSolver::Qr)?;
+    let residuals = &design.y - &design.x.dot(&beta);
+    Ok(residuals.dot(&residuals))
+}
+
+// F-test that `lags` lags of the cause add nothing to `lags` lags of the effect
+fn granger_f(rows: &[LaggedRow], jurisdiction: Option<&str>, cause: &str, effect: &str, lags: usize) -> Result<GrangerTest, Box<dyn Error>> {
+    let cause_lags: Vec<usize> = (1..=lags).collect();
+    let pooled = jurisdiction.is_none();
+    let unrestricted = lag_design(rows, effect, cause, &cause_lags, lags, pooled)?;
+    let restricted = lag_design(rows, effect, cause, &[], lags, pooled)?;
+
+    let rss_u = residual_sum_of_squares(&unrestricted)?;
+    let rss_r = residual_sum_of_squares(&restricted)?;
+    let df2 = rows.len() - unrestricted.names.len();
+    let f_statistic = ((rss_r - rss_u) / lags as f64) / (rss_u / df2 as f64);
+    let f_dist = FisherSnedecor::new(lags as f64, df2 as f64)?;
+
+    Ok(GrangerTest {
+        cause: cause.to_string(),
+        effect: effect.to_string(),
+        jurisdiction: jurisdiction.map(str::to_string),
+        lags,
+        n: rows.len(),
+        f_statistic,
+        df1: lags,
+        df2,
+        p_value: 1.0 - f_dist.cdf(f_statistic),
+    })
+}
+
+// Granger tests of `cause` -> `effect`: one per state, then a pooled test
+// with state fixed effects. With short series the pooled test carries the
+// usual small-T bias of fixed effects with a lagged response. A skipped
+// state's reason names the direction, so results of several calls can be merged.
+pub fn granger_tests(records: &[CleanRecord], cause: &str, effect: &str, lags: usize) -> Result<GrangerResults, Box<dyn Error>> {
+    if lags == 0 {
+        return Err("Granger tests need at least one lag".into());
+    }
+    let panel = Panel::new(records.to_vec());
+    let cause_lags: Vec<usize> = (1..=lags).collect();
+    let rows = lagged_rows(&panel, effect, cause, &cause_lags, lags);
+
+    let mut results = GrangerResults { tests: Vec::new(), skipped: Vec::new() };
+    for state in panel.states() {
+        let state_rows: Vec<LaggedRow> = rows.iter().filter(|r| r.state == *state).cloned().collect();
+        match granger_f(&state_rows, Some(state.as_str()), cause, effect, lags) {
+            Ok(test) => results.tests.push(test),
+            Err(e) => results.skipped.push((state.clone(), format!("{} -> {}: {}", cause, effect, e))),
+        }
+    }
+    results.tests.push(granger_f(&rows, None, cause, effect, lags)?);
+    Ok(results)
+}
+
+// Incarceration -> crime followed by crime -> incarceration
+pub fn granger_both_directions(records: &[CleanRecord], lags: usize) -> Result<GrangerResults, Box<dyn Error>> {
+    let mut results = granger_tests(records, "incarceration_rate", "crime_rate", lags)?;
+    let reverse = granger_tests(records, "crime_rate", "incarceration_rate", lags)?;
+    results.tests.extend(reverse.tests);
+    results.skipped.extend(reverse.skipped);
+    Ok(results)
+}
+
+pub fn print_granger_summary(results: &GrangerResults) {
+    let tests = &results.tests;
+    println!("\n--- Granger causality ---");
+    for t in tests.iter().filter(|t| t.jurisdiction.is_none()) {
+        let significant = tests
+            .iter()
+            .filter(|s| s.jurisdiction.is_some() && s.cause == t.cause && s.effect == t.effect && s.p_value < 0.05)
+            .count();
+        let states = tests.iter().filter(|s| s.jurisdiction.is_some() && s.cause == t.cause && s.effect == t.effect).count();
+        println!(
+            "{} -> {} ({} lags) | Pooled F({}, {}) = {:.3}, p = {:.4} | States with p < 0.05: {}/{}",
+            t.cause, t.effect, t.lags, t.df1, t.df2, t.f_statistic, t.p_value, significant, states
+        );
+    }
+    for (state, reason) in &results.skipped {
+        println!("Skipped {}: {}", state, reason);
+    }
+}
+
+// Lag and autoregressive terms of a fit; the pooled fit's state effects are omitted
+pub fn print_lag_fit(fit: &DistributedLagFit) {
+    let label = fit.jurisdiction.as_deref().unwrap_or("pooled, state fixed effects");
+    println!("\n--- Distributed lag: {} on {} ({}, n = {}) ---", fit.fit.response, fit.predictor, label, fit.fit.n);
+    println!("{:<32} {:>12} {:>10} {:>10} {:>8}", "Term", "Estimate", "SE", "HC3 SE", "p");
+    for c in fit.fit.coefficients.iter().filter(|c| !c.name.starts_with("state_")) {
+        println!("{:<32} {:>12.4} {:>10.4} {:>10} {:>8.4}", c.name, c.estimate, c.std_error, hc3_label(c), c.p_value);
+    }
+    println!("Long-run effect: {:.4}", fit.long_run_effect);
+}
@@ -5,26 +5,68 @@ pub mod nonlinear;
 pub mod petgraph_vis;
 pub mod graph_analysis;
 pub mod state_comparison;
+pub mod sources;
+pub mod validation;
+pub mod panel;
+pub mod states;
+pub mod federal;
+pub mod breaks;
+pub mod harmonization;
+pub mod jails;
+pub mod imputation;
+pub mod polars_pipeline;
+pub mod columnar;
+pub mod sqlite;
+pub mod covariates;
+pub mod anomalies;
+pub mod reconciliation;
+pub mod dataset_diff;
+pub mod manifest;
+pub mod rates;
+pub mod hypothesis;
+pub mod ols;
+pub mod panel_models;
+pub mod lags;
 
 // Re-export commonly used items for easier access in main.rs
-pub use data_processing::{process_dataset, filter_by_state, identify_outliers,CleanRecord};
-pub use graph_analysis::{construct_graph, compute_degree_centrality, compute_average_shortest_path, compute_k_core, group_states_by_centrality};
-pub use calculations::{linear_regression, perform_t_test};
-pub use visualization::{plot_rates, plot_degree_centrality, plot_trends_over_time, plot_national_averages, plot_crime_rates_comparison};
+pub use data_processing::{process_dataset, filter_by_state, identify_outliers, with_crime_measure, CleanRecord, Offense};
+pub use graph_analysis::{GRAPH_THRESHOLD, construct_graph, compute_degree_centrality, compute_average_shortest_path, compute_k_core, group_states_by_centrality};
+pub use calculations::{fit_line, linear_regression, perform_t_test, RegressionFit};
+pub use visualization::{plot_rates, plot_degree_centrality, plot_trends_over_time, plot_national_averages, plot_crime_rates_comparison, plot_federal_trends};
 pub use nonlinear::nonlinear_regression;
-pub use petgraph_vis::{construct_similarity_graph, export_graph, visualize_similarity_graph};
+pub use petgraph_vis::{SIMILARITY_CUTOFF, construct_similarity_graph, export_graph, visualize_similarity_graph};
 pub use state_comparison::compare_states;
+pub use panel::Panel;
+pub use imputation::{exclude_imputed, impute_missing, ImputationStrategy};
+pub use polars_pipeline::{scan_combined, with_rates, yearly_averages, region_averages, records_to_frame, frame_to_records};
+pub use columnar::{write_parquet, write_ipc, read_parquet, read_ipc, panel_frame};
+pub use sqlite::{open_database, write_panel, write_invalid_records, write_validation_issues, write_regression, write_centrality, write_outliers, load_panel};
+pub use covariates::{load_covariates, attach_covariates, with_covariate, construct_feature_similarity_graph, CovariateTable};
+pub use anomalies::{detect_yoy_anomalies, write_anomalies_csv, quarantine, AnomalyConfig, YoyAnomaly};
+pub use reconciliation::{reconcile, reconcile_files, write_mismatches_csv, print_reconciliation_summary, Mismatch, FieldSummary, ReconciliationReport};
+pub use dataset_diff::{diff_panels, diff_datasets, write_changes_csv, print_diff_summary, DatasetDiff, FieldChange};
+pub use manifest::{hash_file, FileHash, RunManifest, RunParameters};
+pub use rates::{apply_rate_spec, Denominator, RateScale, RateSpec};
+pub use hypothesis::{pooled_t_test, welch_t_test, paired_t_test, mann_whitney_u, wilcoxon_signed_rank, paired_by_year, print_test_result, EffectSizeKind, TestKind, TestResult};
+pub use ols::{build_design_matrix, fit_ols, fit_design, print_coefficient_table, Coefficient, DesignMatrix, OlsFit, OlsSolver, OlsSpec, Regressor};
+pub use panel_models::{fit_panel, hausman_test, print_panel_fit, HausmanTest, PanelCoefficient, PanelEstimator, PanelFit};
+pub use lags::{distributed_lag, granger_tests, granger_both_directions, print_granger_summary, print_lag_fit, DistributedLagFit, DistributedLagResults, GrangerResults, GrangerTest, LagStructure};
+pub use jails::{adjust_for_jails, jail_adjustment_report, print_jail_adjustment_report, AnalysisSummary, JailAdjustment};
+pub use harmonization::{harmonize_violent_crime, overlap_ratios, RapeHarmonization, RapeRatios};
+pub use breaks::{analysis_records, apply_break_handling, exclude_flagged, split_at_breaks, series_breaks, BreakHandling, BreakKind, SeriesBreak};
+pub use federal::{load_federal_series, national_totals, allocate_federal_by_population, FederalRecord, NationalTotal};
+pub use states::{lookup_state, resolve_jurisdiction, region_of, Division, Region, StateInfo, FEDERAL};
+pub use validation::{write_issues_csv, write_issues_json, summarize_issues, IssueReason, Severity, ValidationIssue};
+pub use sources::{load_prison_custody, load_ucr, join_sources, rebuild_combined_dataset, CustodyRecord, UcrRecord, CombinedJoin};
 // Optionally, add a shared run function for testing or execution
 pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let (valid_records, invalid_records) = data_processing::process_dataset("crime_and_incarceration_by_state.csv")?;
+    let (valid_records, invalid_records, issues) = data_processing::process_dataset("crime_and_incarceration_by_state.csv")?;
 
-    // Log invalid records
+    // Summarize the rows that were dropped or coerced
     if !invalid_records.is_empty() {
-        println!("The following records have missing or invalid data:");
-        for record in &invalid_records {
-            println!("{:?}", record);
-        }
+        println!("{} records have missing or invalid data.", invalid_records.len());
     }
+    validation::summarize_issues(&issues);
 
     if valid_records.is_empty() {
         eprintln!("No valid records found. Exiting.");
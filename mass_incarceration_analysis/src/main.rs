@@ -1,33 +1,94 @@
 use mass_incarceration_analysis::{
-    process_dataset, filter_by_state, construct_graph, compute_degree_centrality,
+    process_dataset, construct_graph, compute_degree_centrality,
     compute_average_shortest_path, compute_k_core, group_states_by_centrality,
     linear_regression, plot_rates, plot_degree_centrality, plot_trends_over_time,
     plot_national_averages, nonlinear_regression, export_graph, identify_outliers, compare_states,
-    plot_crime_rates_comparison, perform_t_test,
+    plot_crime_rates_comparison, Panel, load_federal_series, national_totals,
+    plot_federal_trends, exclude_flagged, analysis_records, BreakHandling, harmonize_violent_crime, RapeHarmonization,
+    jail_adjustment_report, print_jail_adjustment_report, JailAdjustment, impute_missing,
+    ImputationStrategy, summarize_issues, write_issues_csv, write_issues_json, write_parquet, write_ipc,
+    open_database, write_panel, write_invalid_records, write_validation_issues, write_regression,
+    write_centrality, write_outliers, load_covariates, attach_covariates, with_covariate,
+    detect_yoy_anomalies, write_anomalies_csv, AnomalyConfig, reconcile_files, write_mismatches_csv,
+    print_reconciliation_summary, diff_datasets, print_diff_summary, write_changes_csv, RunManifest,
+    RunParameters, GRAPH_THRESHOLD, SIMILARITY_CUTOFF, apply_rate_spec, RateSpec, paired_by_year,
+    pooled_t_test, welch_t_test, paired_t_test, mann_whitney_u, wilcoxon_signed_rank, print_test_result,
+    TestResult, fit_ols, print_coefficient_table, OlsSpec, Regressor, Offense,
+    fit_panel, hausman_test, print_panel_fit, PanelEstimator,
+    distributed_lag, granger_both_directions, print_granger_summary, print_lag_fit, LagStructure,
 };
 
+const K_CORE: usize = 3;
+const STATES_COMPARED: [&str; 2] = ["Arizona", "Massachusetts"];
+// Treatment of reporting-change and estimated years in every analysis below
+const BREAK_HANDLING: BreakHandling = BreakHandling::Include;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let _start = std::time::Instant::now();
     std::fs::create_dir_all("output")?;
     
+    // `reconcile` checks the combined file against its two sources and exits
+    if std::env::args().nth(1).as_deref() == Some("reconcile") {
+        let report = reconcile_files(
+            "prison_custody_by_state.csv",
+            "ucr_by_state.csv",
+            "crime_and_incarceration_by_state.csv",
+            0.0,
+        )?;
+        print_reconciliation_summary(&report);
+        write_mismatches_csv(&report.mismatches, "output/reconciliation_mismatches.csv")?;
+        println!("{} mismatches saved to 'output/reconciliation_mismatches.csv'", report.mismatches.len());
+        return Ok(());
+    }
+
+    // `diff <old.csv> <new.csv>` reports what changed between two releases and exits
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() == 4 && args[1] == "diff" {
+        let diff = diff_datasets(&args[2], &args[3])?;
+        print_diff_summary(&diff);
+        write_changes_csv(&diff.changes, "output/dataset_changes.csv")?;
+        write_changes_csv(&diff.rate_movements, "output/dataset_rate_movements.csv")?;
+        println!("Changes saved to 'output/dataset_changes.csv' and 'output/dataset_rate_movements.csv'");
+        return Ok(());
+    }
+
     // Check dataset existence
     if !std::path::Path::new("crime_and_incarceration_by_state.csv").exists() {
         eprintln!("Dataset file not found. Please ensure the file is present.");
         return Ok(());
     }
 
+    // Hash the inputs and record the parameters before anything is written
+    let rate_spec = RateSpec::default();
+    let mut inputs = vec!["crime_and_incarceration_by_state.csv"];
+    if std::path::Path::new("covariates.csv").exists() {
+        inputs.push("covariates.csv");
+    }
+    let mut manifest = RunManifest::start(
+        &inputs,
+        RunParameters {
+            graph_threshold: GRAPH_THRESHOLD,
+            similarity_cutoff: SIMILARITY_CUTOFF,
+            k_core: K_CORE,
+            states_compared: STATES_COMPARED.iter().map(|s| s.to_string()).collect(),
+            rate_spec: rate_spec.label(),
+            break_handling: BREAK_HANDLING,
+        },
+    )?;
+
     // Step 1: Process the dataset
     println!("Processing dataset...");
-    let (records, invalid_records) = process_dataset("crime_and_incarceration_by_state.csv")?;
+    let (records, invalid_records, issues) = process_dataset("crime_and_incarceration_by_state.csv")?;
 
-    // Log invalid records
+    // Export validation issues for triage instead of dumping every bad row
     if !invalid_records.is_empty() {
-        println!("The following records have missing or invalid data:");
-        for record in &invalid_records {
-            println!("{:?}", record);
-        }
+        println!("{} records have missing or invalid data.", invalid_records.len());
     }
+    summarize_issues(&issues);
+    write_issues_csv(&issues, "output/validation_issues.csv")?;
+    write_issues_json(&issues, "output/validation_issues.json")?;
+    println!("Validation issues saved to 'output/validation_issues.csv' and 'output/validation_issues.json'");
 
     // If no valid records, terminate the program
     if records.is_empty() {
@@ -35,9 +96,117 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    // Index the records by (state, year); the panel derefs to &[CleanRecord]
+    let records = Panel::new(apply_rate_spec(&records, &rate_spec));
+    if !records.is_balanced() {
+        println!("Panel is unbalanced: {} state-years missing.", records.missing_cells().len());
+    }
+
+    // Persist the cleaned panel for notebooks and downstream jobs
+    write_parquet(&records, &issues, "output/cleaned_panel.parquet")?;
+    write_ipc(&records, &issues, "output/cleaned_panel.arrow")?;
+    println!("Cleaned panel saved to 'output/cleaned_panel.parquet' and 'output/cleaned_panel.arrow'");
+
+    // Year-over-year jumps for manual review; quarantine() drops them from analyses
+    let anomalies = detect_yoy_anomalies(&records, AnomalyConfig::default());
+    write_anomalies_csv(&anomalies, "output/anomalies.csv")?;
+    println!("{} year-over-year anomalies saved to 'output/anomalies.csv'", anomalies.len());
+
+    // Same data, plus the analysis outputs below, in SQLite for ad-hoc queries
+    // Rebuilt every run, so a database from an older schema never lingers
+    let _ = std::fs::remove_file("output/analysis.sqlite");
+    let mut db = open_database("output/analysis.sqlite")?;
+    write_panel(&mut db, &records)?;
+    write_invalid_records(&mut db, &invalid_records)?;
+    write_validation_issues(&mut db, &issues)?;
+
+    // Every analysis from here on sees the records under the chosen break handling
+    let records = Panel::new(analysis_records(&records, BREAK_HANDLING));
+    println!("Break handling: {:?} ({} state-years analysed)", BREAK_HANDLING, records.len());
+
     // Step 2: Perform linear regression
     println!("Performing linear regression...");
-    linear_regression(&records)?;
+    let fit = linear_regression(&records)?;
+    write_regression(&mut db, "all states", fit.slope as f32, fit.intercept as f32, fit.n)?;
+    std::fs::write("output/linear_regression.json", fit.to_json()?)?;
+
+    // Re-run without years flagged as reporting changes or FBI estimates
+    if BREAK_HANDLING == BreakHandling::Include {
+        println!("Performing linear regression excluding flagged years...");
+        linear_regression(&exclude_flagged(&records))?;
+    }
+
+    // Back-cast the 2013 rape definition so the violent total has no jump
+    println!("Performing linear regression on harmonized violent crime...");
+    linear_regression(&harmonize_violent_crime(&records, RapeHarmonization::RevisedBackcast))?;
+
+    // Optional external covariates: a state column, a year column and one column per covariate
+    if std::path::Path::new("covariates.csv").exists() {
+        let table = load_covariates("covariates.csv")?;
+        let with_covariates = attach_covariates(&records, &table);
+        for name in &table.names {
+            let subset = with_covariate(&with_covariates, name);
+            if !subset.is_empty() {
+                println!("Performing linear regression on {}...", name);
+                linear_regression(&subset)?;
+            }
+        }
+    }
+
+    // Multiple regression of incarceration on both crime categories, a linear
+    // trend and region dummies, with robust standard errors and VIFs
+    println!("Performing multiple OLS regression...");
+    let spec = OlsSpec::new(
+        "incarceration_rate",
+        vec![
+            Regressor::feature("crime_rate"),
+            Regressor::OffenseRate(Offense::PropertyTotal),
+            Regressor::Year,
+            Regressor::RegionDummies,
+        ],
+    );
+    let ols = fit_ols(&records, &spec)?;
+    print_coefficient_table(&ols);
+    std::fs::write("output/ols_regression.json", ols.to_json()?)?;
+
+    // Panel estimators: compare states with themselves over time rather than
+    // with each other, with standard errors clustered by state
+    println!("Fitting panel fixed- and random-effects models...");
+    let panel_spec = OlsSpec::new("crime_rate", vec![Regressor::feature("incarceration_rate")]);
+    let within = fit_panel(&records, &panel_spec, PanelEstimator::Within)?;
+    let two_way = fit_panel(&records, &panel_spec, PanelEstimator::TwoWay)?;
+    let random = fit_panel(&records, &panel_spec, PanelEstimator::RandomEffects)?;
+    for fit in [&within, &two_way, &random] {
+        print_panel_fit(fit);
+    }
+    let hausman = hausman_test(&within, &random)?;
+    println!(
+        "Hausman test (FE vs RE): chi2({}) = {:.3}, p = {:.4}",
+        hausman.df, hausman.statistic, hausman.p_value
+    );
+    let panel_models = serde_json::json!({
+        "fits": [&within, &two_way, &random],
+        "hausman": hausman,
+    });
+    std::fs::write("output/panel_models.json", serde_json::to_string_pretty(&panel_models)?)?;
+
+    // Delayed effects: crime on this and the previous two years of
+    // incarceration, then Granger tests in both directions
+    println!("Fitting distributed-lag models and Granger tests...");
+    let lag_fits = distributed_lag(&records, "crime_rate", "incarceration_rate", &LagStructure::up_to(2))?;
+    if let Some(pooled) = lag_fits.fits.last() {
+        print_lag_fit(pooled);
+    }
+    for (state, reason) in &lag_fits.skipped {
+        println!("Distributed lag skipped {}: {}", state, reason);
+    }
+    let granger = granger_both_directions(&records, 2)?;
+    print_granger_summary(&granger);
+    let lag_models = serde_json::json!({
+        "distributed_lag": lag_fits,
+        "granger": granger,
+    });
+    std::fs::write("output/lag_models.json", serde_json::to_string_pretty(&lag_models)?)?;
 
     // Step 3: Plot average rates
     println!("Plotting average rates...");
@@ -47,13 +216,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Performing nonlinear regression...");
     nonlinear_regression(&records)?;
 
-    // Step 5: Filter data for specific states and plot trends
-    for state in &["Arizona", "Massachusetts"] {
-        let state_data = filter_by_state(&records, state);
-        if state_data.is_empty() {
+    // Step 5: Filter data for specific states and plot trends, with gaps
+    // filled by interpolation and marked on the chart
+    let filled = apply_rate_spec(&impute_missing(&records, &invalid_records, ImputationStrategy::Linear), &rate_spec);
+    println!("Imputed {} missing state-years.", filled.iter().filter(|r| r.is_imputed()).count());
+    for state in &STATES_COMPARED {
+        if records.series(state).is_empty() {
             eprintln!("No data found for {}.", state);
         } else {
-            plot_trends_over_time(&records, Some(state))?;
+            plot_trends_over_time(&filled, Some(state))?;
         }
     }
 
@@ -63,10 +234,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Graph has {} nodes and {} edges.", graph.node_count(), graph.edge_count());
 
     println!("Exporting graph...");
-    export_graph(&graph, "graph.dot")?;
+    export_graph(&graph, "output/graph.dot")?;
     println!(
-        "Graph exported to 'graph.dot'. Use the following command to visualize:\n\
-         dot -Tpng graph.dot -o graph.png"
+        "Graph exported to 'output/graph.dot'. Use the following command to visualize:\n\
+         dot -Tpng output/graph.dot -o graph.png"
     );
 
     // Step 7: Compute degree centrality and plot
@@ -77,13 +248,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("State: {:<15} | Degree: {}", state, degree);
     }
     plot_degree_centrality(&degree_centrality)?;
+    write_centrality(&mut db, &degree_centrality)?;
 
     // Step 8: Analyze shortest path and k-core
     let avg_shortest_path = compute_average_shortest_path(&graph);
     println!("Average Shortest Path Length: {:.4}", avg_shortest_path);
 
-    let k_core = compute_k_core(&graph, 3);
-    println!("3-Core Subgraph Nodes: {:?}", k_core);
+    let k_core = compute_k_core(&graph, K_CORE);
+    println!("{}-Core Subgraph Nodes: {:?}", K_CORE, k_core);
 
     // Step 9: Categorize states by centrality
     let (high, medium, low) = group_states_by_centrality(&degree_centrality);
@@ -95,25 +267,55 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Plotting nationwide trends...");
     plot_national_averages(&records)?;
 
+    // Federal prisoners are kept out of the state rates but count toward national totals
+    let federal = load_federal_series("crime_and_incarceration_by_state.csv")?;
+    let totals = national_totals(&records, &federal);
+    for total in &totals {
+        println!(
+            "Year {} | State: {} | Federal: {} | Rate (state + federal): {:.2}",
+            total.year, total.state_prisoners, total.federal_prisoners, total.incarceration_rate
+        );
+    }
+    plot_federal_trends(&totals)?;
+
     let outliers = identify_outliers(&records);
     println!("Outliers: {:?}", outliers);
+    write_outliers(&mut db, &outliers)?;
+    println!("Panel and analysis outputs saved to 'output/analysis.sqlite'");
+
+    // Check how much the unified prison/jail states move the results
+    let jail_report = jail_adjustment_report(&records, JailAdjustment::Separate);
+    print_jail_adjustment_report(&jail_report);
 
     // Step 11: Compare Arizona and Massachusetts crime rates
-    let (arizona_data, massachusetts_data) = compare_states(&records, "Arizona", "Massachusetts");
+    let (arizona_data, massachusetts_data) = compare_states(&records, STATES_COMPARED[0], STATES_COMPARED[1])?;
     println!("Arizona Data: {:?}", arizona_data);
     println!("Massachusetts Data: {:?}", massachusetts_data);
 
-    plot_crime_rates_comparison(&records, &["Arizona", "Massachusetts"])?;
+    plot_crime_rates_comparison(&records, &STATES_COMPARED)?;
 
-    // Step 12: Perform t-test
+    // Step 12: Two-sample tests. The states are paired by year, so the paired
+    // and signed-rank tests are the primary ones; Welch allows unequal variances.
     let crime_rates_az: Vec<f64> = arizona_data.iter().map(|r| r.crime_rate as f64).collect();
     let crime_rates_ma: Vec<f64> = massachusetts_data.iter().map(|r| r.crime_rate as f64).collect();
+    let (paired_az, paired_ma) = paired_by_year(&arizona_data, &massachusetts_data, |r| r.crime_rate as f64);
 
-    let (t_stat, p_value) = perform_t_test(&crime_rates_az, &crime_rates_ma)?;
-    println!(
-        "T-Test Results: t-statistic = {:.4}, p-value = {:.4}",
-        t_stat, p_value
-    );
+    let tests = [
+        ("Pooled t-test", pooled_t_test(&crime_rates_az, &crime_rates_ma)?),
+        ("Welch t-test", welch_t_test(&crime_rates_az, &crime_rates_ma)?),
+        ("Paired t-test", paired_t_test(&paired_az, &paired_ma)?),
+        ("Mann-Whitney U", mann_whitney_u(&crime_rates_az, &crime_rates_ma)?),
+        ("Wilcoxon signed-rank", wilcoxon_signed_rank(&paired_az, &paired_ma)?),
+    ];
+    for (label, result) in &tests {
+        print_test_result(label, result);
+    }
+    let results: Vec<&TestResult> = tests.iter().map(|(_, result)| result).collect();
+    std::fs::write("output/hypothesis_tests.json", serde_json::to_string_pretty(&results)?)?;
+
+    manifest.finish("output")?;
+    manifest.write("output/run_manifest.json")?;
+    println!("Run manifest saved to 'output/run_manifest.json' ({} artifacts)", manifest.artifacts.len());
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,100 @@
+use crate::breaks::BreakHandling;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileHash {
+    pub path: String,
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+// Every knob main uses that changes the outputs
+#[derive(Debug, Clone, Serialize)]
+pub struct RunParameters {
+    pub graph_threshold: f32,
+    pub similarity_cutoff: f32,
+    pub k_core: usize,
+    pub states_compared: Vec<String>,
+    pub rate_spec: String, // RateSpec::label()
+    pub break_handling: BreakHandling,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunManifest {
+    pub crate_version: String,
+    pub started_at: u64, // Unix seconds
+    pub finished_at: u64,
+    pub inputs: Vec<FileHash>,
+    pub parameters: RunParameters,
+    pub artifacts: Vec<FileHash>,
+    #[serde(skip)]
+    started: SystemTime,
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn hash_file(path: &str) -> Result<FileHash, Box<dyn Error>> {
+    let bytes = fs::read(path)?;
+    let digest = Sha256::digest(&bytes);
+    Ok(FileHash {
+        path: path.to_string(),
+        sha256: digest.iter().map(|b| format!("{:02x}", b)).collect(),
+        bytes: bytes.len() as u64,
+    })
+}
+
+impl RunManifest {
+    // Call before producing any output; inputs are hashed immediately
+    pub fn start(inputs: &[&str], parameters: RunParameters) -> Result<Self, Box<dyn Error>> {
+        let started = SystemTime::now();
+        Ok(RunManifest {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: unix_seconds(started),
+            finished_at: 0,
+            inputs: inputs.iter().map(|p| hash_file(p)).collect::<Result<_, _>>()?,
+            parameters,
+            artifacts: Vec::new(),
+            started,
+        })
+    }
+
+    // Hash every file under `output_dir` written since start(); files left
+    // over from earlier runs are not listed
+    pub fn finish(&mut self, output_dir: &str) -> Result<(), Box<dyn Error>> {
+        // Whole seconds, for filesystems with coarse modification times
+        let since = UNIX_EPOCH + Duration::from_secs(unix_seconds(self.started));
+        let mut artifacts = Vec::new();
+        collect_artifacts(Path::new(output_dir), since, &mut artifacts)?;
+        artifacts.sort();
+
+        self.artifacts = artifacts.iter().map(|p| hash_file(p)).collect::<Result<_, _>>()?;
+        self.finished_at = unix_seconds(SystemTime::now());
+        Ok(())
+    }
+
+    pub fn write(&self, output_path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(output_path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn collect_artifacts(dir: &Path, since: SystemTime, found: &mut Vec<String>) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_artifacts(&path, since, found)?;
+        } else if metadata.modified()? >= since {
+            found.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,378 @@
+use crate::data_processing::{CleanRecord, Offense};
+use crate::states::{region_of, Region};
+use nalgebra::DMatrix;
+use ndarray::{Array1, Array2, Axis};
+use serde::Serialize;
+use statrs::distribution::{ContinuousCDF, FisherSnedecor, StudentsT};
+use std::error::Error;
+
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+// Regions that get an indicator column; Northeast is the base level
+const REGION_DUMMIES: [Region; 3] = [Region::Midwest, Region::South, Region::West];
+
+// One term of the model. A term can expand to several columns (region
+// dummies) and an interaction multiplies every pair of its sides' columns.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Regressor {
+    Feature(String), // incarceration_rate, crime_rate or a covariate (CleanRecord::feature)
+    OffenseRate(Offense),
+    Year,
+    RegionDummies,
+    Interaction(Box<Regressor>, Box<Regressor>),
+}
+
+impl Regressor {
+    pub fn feature(name: &str) -> Self {
+        Regressor::Feature(name.to_string())
+    }
+
+    pub fn interaction(a: Regressor, b: Regressor) -> Self {
+        Regressor::Interaction(Box::new(a), Box::new(b))
+    }
+
+    pub fn column_names(&self) -> Vec<String> {
+        match self {
+            Regressor::Feature(name) => vec![name.clone()],
+            Regressor::OffenseRate(offense) => vec![offense.rate_field().to_string()],
+            Regressor::Year => vec!["year".to_string()],
+            Regressor::RegionDummies => REGION_DUMMIES.iter().map(|r| format!("region_{:?}", r)).collect(),
+            Regressor::Interaction(a, b) => {
+                let right = b.column_names();
+                a.column_names()
+                    .iter()
+                    .flat_map(|l| right.iter().map(move |r| format!("{}:{}", l, r)))
+                    .collect()
+            }
+        }
+    }
+
+    // Column values for one record; None if the record lacks any of them
+    pub fn values(&self, record: &CleanRecord) -> Option<Vec<f64>> {
+        match self {
+            Regressor::Feature(name) => record.feature(name).map(|v| vec![v]),
+            Regressor::OffenseRate(offense) => record.offense_rate(*offense).map(|v| vec![v as f64]),
+            Regressor::Year => Some(vec![record.year as f64]),
+            Regressor::RegionDummies => {
+                let region = region_of(&record.jurisdiction)?;
+                Some(REGION_DUMMIES.iter().map(|r| if *r == region { 1.0 } else { 0.0 }).collect())
+            }
+            Regressor::Interaction(a, b) => {
+                let right = b.values(record)?;
+                let left = a.values(record)?;
+                Some(left.iter().flat_map(|l| right.iter().map(move |r| l * r)).collect())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum OlsSolver {
+    #[default]
+    Qr,
+    Svd, // Minimum-norm least squares; also reports the numerical rank
+}
+
+#[derive(Debug, Clone)]
+pub struct OlsSpec {
+    pub response: String, // Any name CleanRecord::feature knows
+    pub regressors: Vec<Regressor>,
+    pub intercept: bool,
+    pub solver: OlsSolver,
+}
+
+impl OlsSpec {
+    pub fn new(response: &str, regressors: Vec<Regressor>) -> Self {
+        OlsSpec { response: response.to_string(), regressors, intercept: true, solver: OlsSolver::default() }
+    }
+}
+
+// Records with every regressor and the response present, as a matrix
+#[derive(Debug, Clone)]
+pub struct DesignMatrix {
+    pub names: Vec<String>,
+    pub x: Array2<f64>,
+    pub y: Array1<f64>,
+    pub rows: Vec<(String, u32)>, // State-year of each row
+    pub dropped: usize,           // Records missing a value
+}
+
+pub fn build_design_matrix(records: &[CleanRecord], spec: &OlsSpec) -> Result<DesignMatrix, Box<dyn Error>> {
+    let mut names = Vec::new();
+    if spec.intercept {
+        names.push("intercept".to_string());
+    }
+    names.extend(spec.regressors.iter().flat_map(|r| r.column_names()));
+    let k = names.len();
+
+    let mut values = Vec::new();
+    let mut y = Vec::new();
+    let mut rows = Vec::new();
+    for record in records {
+        let row: Option<Vec<Vec<f64>>> = spec.regressors.iter().map(|r| r.values(record)).collect();
+        let (Some(row), Some(response)) = (row, record.feature(&spec.response)) else {
+            continue;
+        };
+        let row: Vec<f64> = row.concat();
+        if !response.is_finite() || row.iter().any(|v| !v.is_finite()) {
+            continue;
+        }
+        if spec.intercept {
+            values.push(1.0);
+        }
+        values.extend(row);
+        y.push(response);
+        rows.push((record.jurisdiction.clone(), record.year));
+    }
+
+    let n = y.len();
+    if n <= k {
+        return Err(format!("OLS needs more complete records ({}) than columns ({})", n, k).into());
+    }
+    Ok(DesignMatrix {
+        names,
+        x: Array2::from_shape_vec((n, k), values)?,
+        y: Array1::from(y),
+        rows,
+        dropped: records.len() - n,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Coefficient {
+    pub name: String,
+    pub estimate: f64,
+    pub std_error: f64,
+    pub t_value: f64,
+    pub p_value: f64,
+    pub ci: (f64, f64),
+    pub hc1_se: f64, // White standard error with the n / (n - k) correction
+    pub hc1_p: f64,
+    pub hc3_se: Option<f64>, // Squared residuals scaled by 1 / (1 - leverage)^2; None if a leverage is 1
+    pub hc3_p: Option<f64>,
+    pub vif: Option<f64>, // None for the intercept
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OlsFit {
+    pub response: String,
+    pub solver: OlsSolver,
+    pub n: usize,
+    pub df_resid: usize,
+    pub dropped: usize,
+    pub confidence_level: f64,
+    pub coefficients: Vec<Coefficient>,
+    pub r_squared: f64,
+    pub adj_r_squared: f64,
+    pub residual_std_error: f64,
+    pub f_statistic: f64, // All slopes zero; NaN without slopes
+    pub f_p_value: f64,
+    pub fitted: Vec<f64>,
+    pub residuals: Vec<f64>,
+}
+
+impl OlsFit {
+    pub fn coefficient(&self, name: &str) -> Option<&Coefficient> {
+        self.coefficients.iter().find(|c| c.name == name)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+fn to_nalgebra(a: &Array2<f64>) -> DMatrix<f64> {
+    DMatrix::from_fn(a.nrows(), a.ncols(), |i, j| a[[i, j]])
+}
+
+fn from_nalgebra(m: &DMatrix<f64>) -> Array2<f64> {
+    Array2::from_shape_fn((m.nrows(), m.ncols()), |(i, j)| m[(i, j)])
+}
+
+pub(crate) fn inverse(a: &Array2<f64>) -> Result<Array2<f64>, Box<dyn Error>> {
+    let inv = to_nalgebra(a).try_inverse().ok_or("Matrix is singular")?;
+    Ok(from_nalgebra(&inv))
+}
+
+// Coefficients and (X'X)^-1 by the chosen solver. Both reject rank-deficient
+// designs, e.g. a dummy for every region alongside the intercept.
+pub(crate) fn solve(x: &Array2<f64>, y: &Array1<f64>, solver: OlsSolver) -> Result<(Array1<f64>, Array2<f64>), Box<dyn Error>> {
+    let k = x.ncols();
+    let (xm, ym) = (to_nalgebra(x), DMatrix::from_fn(y.len(), 1, |i, _| y[i]));
+    match solver {
+        OlsSolver::Qr => {
+            let qr = xm.qr();
+            let (q, r) = (qr.q(), qr.r());
+            let diagonal = r.diagonal().map(f64::abs);
+            let largest = diagonal.max();
+            if diagonal.iter().any(|d| *d <= largest * 1e-10) {
+                return Err("Design matrix is rank deficient".into());
+            }
+            let r_inv = r.try_inverse().ok_or("Design matrix is rank deficient")?;
+            let beta = &r_inv * (q.transpose() * ym);
+            let xtx_inv = &r_inv * r_inv.transpose();
+            Ok((Array1::from_iter(beta.iter().copied()), from_nalgebra(&xtx_inv)))
+        }
+        OlsSolver::Svd => {
+            let svd = xm.svd(true, true);
+            let tolerance = 1e-12 * svd.singular_values.max();
+            let rank = svd.rank(tolerance);
+            if rank < k {
+                return Err(format!("Design matrix is rank deficient (rank {} of {})", rank, k).into());
+            }
+            let beta = svd.solve(&ym, tolerance)?;
+            Ok((Array1::from_iter(beta.iter().copied()), inverse(&x.t().dot(x))?))
+        }
+    }
+}
+
+// Sandwich (X'X)^-1 X' diag(w) X (X'X)^-1
+fn sandwich(x: &Array2<f64>, xtx_inv: &Array2<f64>, weights: &Array1<f64>) -> Array2<f64> {
+    let weighted = x * &weights.view().insert_axis(Axis(1));
+    let meat = x.t().dot(&weighted);
+    xtx_inv.dot(&meat).dot(xtx_inv)
+}
+
+// Variance inflation factors of the non-intercept columns: the diagonal of
+// the inverse correlation matrix, i.e. 1 / (1 - R^2) of each column on the others
+fn variance_inflation(x: &Array2<f64>, columns: &[usize]) -> Result<Vec<f64>, Box<dyn Error>> {
+    if columns.len() < 2 {
+        return Ok(vec![1.0; columns.len()]);
+    }
+    let sub = x.select(Axis(1), columns);
+    let n = sub.nrows() as f64;
+    let mean = sub.mean_axis(Axis(0)).ok_or("Empty design matrix")?;
+    let centered = &sub - &mean;
+    let sd = centered.mapv(|v| v * v).sum_axis(Axis(0)).mapv(|s| (s / (n - 1.0)).sqrt());
+    if sd.iter().any(|s| *s == 0.0) {
+        return Err("A regressor is constant; remove it or the intercept".into());
+    }
+    let z = centered / &sd;
+    let correlation = z.t().dot(&z) / (n - 1.0);
+    Ok(inverse(&correlation)?.diag().to_vec())
+}
+
+pub fn fit_ols(records: &[CleanRecord], spec: &OlsSpec) -> Result<OlsFit, Box<dyn Error>> {
+    let design = build_design_matrix(records, spec)?;
+    fit_design(&design, spec)
+}
+
+pub fn fit_design(design: &DesignMatrix, spec: &OlsSpec) -> Result<OlsFit, Box<dyn Error>> {
+    let (x, y) = (&design.x, &design.y);
+    let (n, k) = x.dim();
+    if n <= k {
+        return Err(format!("{} observations for {} parameters", n, k).into());
+    }
+    let df = (n - k) as f64;
+
+    let (beta, xtx_inv) = solve(x, y, spec.solver)?;
+    let fitted = x.dot(&beta);
+    let residuals = y - &fitted;
+
+    let rss = residuals.dot(&residuals);
+    let tss = if spec.intercept {
+        let mean = y.mean().unwrap_or(0.0);
+        y.mapv(|v| (v - mean).powi(2)).sum()
+    } else {
+        y.dot(y)
+    };
+    let r_squared = if tss > 0.0 { 1.0 - rss / tss } else { 1.0 };
+    let df_model = k - spec.intercept as usize;
+    let df_total = (if spec.intercept { n - 1 } else { n }) as f64;
+    let adj_r_squared = 1.0 - (1.0 - r_squared) * df_total / df;
+    let sigma2 = rss / df;
+
+    let t_dist = StudentsT::new(0.0, 1.0, df)?;
+    let t_crit = t_dist.inverse_cdf(1.0 - (1.0 - CONFIDENCE_LEVEL) / 2.0);
+    let p_value = |t: f64| 2.0 * (1.0 - t_dist.cdf(t.abs()));
+
+    let (f_statistic, f_p_value) = if df_model > 0 {
+        let f = ((tss - rss) / df_model as f64) / sigma2;
+        let f_dist = FisherSnedecor::new(df_model as f64, df)?;
+        (f, 1.0 - f_dist.cdf(f))
+    } else {
+        (f64::NAN, f64::NAN)
+    };
+
+    // Leverages h_ii = x_i (X'X)^-1 x_i'
+    let leverage = (x.dot(&xtx_inv) * x).sum_axis(Axis(1));
+    let squared = residuals.mapv(|e| e * e);
+    let hc1 = sandwich(x, &xtx_inv, &squared) * (n as f64 / df);
+    // An observation fitted exactly (e.g. the only row of a dummy) has
+    // leverage 1, where HC3 is undefined
+    let hc3 = if leverage.iter().all(|h| 1.0 - h > 1e-8) {
+        let hc3_weights = &squared / &leverage.mapv(|h| (1.0 - h).powi(2));
+        Some(sandwich(x, &xtx_inv, &hc3_weights))
+    } else {
+        None
+    };
+
+    let slope_columns: Vec<usize> = (spec.intercept as usize..k).collect();
+    let vifs = variance_inflation(x, &slope_columns)?;
+
+    let coefficients = design
+        .names
+        .iter()
+        .enumerate()
+        .map(|(j, name)| {
+            let estimate = beta[j];
+            let std_error = (sigma2 * xtx_inv[[j, j]]).sqrt();
+            let hc1_se = hc1[[j, j]].sqrt();
+            let hc3_se = hc3.as_ref().map(|hc3| hc3[[j, j]].sqrt());
+            Coefficient {
+                name: name.clone(),
+                estimate,
+                std_error,
+                t_value: estimate / std_error,
+                p_value: p_value(estimate / std_error),
+                ci: (estimate - t_crit * std_error, estimate + t_crit * std_error),
+                hc1_se,
+                hc1_p: p_value(estimate / hc1_se),
+                hc3_se,
+                hc3_p: hc3_se.map(|se| p_value(estimate / se)),
+                vif: slope_columns.iter().position(|c| *c == j).map(|i| vifs[i]),
+            }
+        })
+        .collect();
+
+    Ok(OlsFit {
+        response: spec.response.clone(),
+        solver: spec.solver,
+        n,
+        df_resid: n - k,
+        dropped: design.dropped,
+        confidence_level: CONFIDENCE_LEVEL,
+        coefficients,
+        r_squared,
+        adj_r_squared,
+        residual_std_error: sigma2.sqrt(),
+        f_statistic,
+        f_p_value,
+        fitted: fitted.to_vec(),
+        residuals: residuals.to_vec(),
+    })
+}
+
+// HC3 standard error for a table, "n/a" where it is undefined
+pub(crate) fn hc3_label(c: &Coefficient) -> String {
+    c.hc3_se.map(|se| format!("{:.4}", se)).unwrap_or_else(|| "n/a".to_string())
+}
+
+pub fn print_coefficient_table(fit: &OlsFit) {
+    println!("\n--- OLS: {} (n = {}, {} dropped) ---", fit.response, fit.n, fit.dropped);
+    println!(
+        "{:<32} {:>12} {:>10} {:>8} {:>8} {:>10} {:>10} {:>8}",
+        "Term", "Estimate", "SE", "t", "p", "HC1 SE", "HC3 SE", "VIF"
+    );
+    for c in &fit.coefficients {
+        let vif = c.vif.map(|v| format!("{:.2}", v)).unwrap_or_default();
+        println!(
+            "{:<32} {:>12.4} {:>10.4} {:>8.2} {:>8.4} {:>10.4} {:>10} {:>8}",
+            c.name, c.estimate, c.std_error, c.t_value, c.p_value, c.hc1_se, hc3_label(c), vif
+        );
+    }
+    println!(
+        "R-squared: {:.4} | Adj. R-squared: {:.4} | Residual SE: {:.4} | F: {:.2} (p = {:.4})",
+        fit.r_squared, fit.adj_r_squared, fit.residual_std_error, fit.f_statistic, fit.f_p_value
+    );
+}
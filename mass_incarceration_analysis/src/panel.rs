@@ -0,0 +1,141 @@
+use crate::data_processing::{normalize_jurisdiction, CleanRecord};
+use crate::states::lookup_state;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{Deref, Range};
+
+// State-year panel. Records are stored sorted by (canonical state, year) so
+// each state's time series is a contiguous slice, and the panel derefs to
+// &[CleanRecord] so every existing analysis function accepts it directly.
+// States are normalized once when the panel is built; lookups go through
+// `aliases` to a state id and then to the record, without scanning the registry.
+#[derive(Debug, Clone, Default)]
+pub struct Panel {
+    records: Vec<CleanRecord>,
+    index: HashMap<(usize, u32), usize>, // (state id, year) -> record
+    series: Vec<Range<usize>>,           // By state id
+    states: Vec<String>,                 // Canonical name by state id
+    aliases: HashMap<String, usize>,     // Canonical, title-case and postal spellings -> state id
+    years: Vec<u32>,
+}
+
+impl Panel {
+    // Duplicate (state, year) rows keep the first occurrence
+    pub fn new(records: Vec<CleanRecord>) -> Self {
+        let mut keyed: Vec<(String, CleanRecord)> =
+            records.into_iter().map(|r| (normalize_jurisdiction(&r.jurisdiction), r)).collect();
+        keyed.sort_by(|(a, ra), (b, rb)| a.cmp(b).then(ra.year.cmp(&rb.year)));
+        keyed.dedup_by(|(later, rl), (earlier, re)| later == earlier && rl.year == re.year);
+
+        let mut panel = Panel::default();
+        let mut years = BTreeSet::new();
+        for (i, (state, record)) in keyed.into_iter().enumerate() {
+            if panel.states.last() != Some(&state) {
+                let id = panel.states.len();
+                if let Some(info) = lookup_state(&state) {
+                    panel.aliases.insert(info.display.to_string(), id);
+                    panel.aliases.insert(info.postal.to_string(), id);
+                }
+                panel.aliases.insert(state.clone(), id);
+                panel.states.push(state);
+                panel.series.push(i..i);
+            }
+            let id = panel.states.len() - 1;
+            panel.series[id].end = i + 1;
+            panel.index.insert((id, record.year), i);
+            years.insert(record.year);
+            panel.records.push(record);
+        }
+        panel.years = years.into_iter().collect();
+
+        panel
+    }
+
+    // Other spellings fall back to the state registry
+    fn state_id(&self, state: &str) -> Option<usize> {
+        match self.aliases.get(state) {
+            Some(&id) => Some(id),
+            None => self.aliases.get(&normalize_jurisdiction(state)).copied(),
+        }
+    }
+
+    pub fn records(&self) -> &[CleanRecord] {
+        &self.records
+    }
+
+    pub fn into_records(self) -> Vec<CleanRecord> {
+        self.records
+    }
+
+    // Canonical (upper-case) state names, sorted
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+
+    pub fn years(&self) -> &[u32] {
+        &self.years
+    }
+
+    pub fn get(&self, state: &str, year: u32) -> Option<&CleanRecord> {
+        let id = self.state_id(state)?;
+        self.index.get(&(id, year)).map(|&i| &self.records[i])
+    }
+
+    // One state's records, sorted by year (empty if the state is unknown)
+    pub fn series(&self, state: &str) -> &[CleanRecord] {
+        match self.state_id(state) {
+            Some(id) => &self.records[self.series[id].clone()],
+            None => &[],
+        }
+    }
+
+    // Every state's record for one year, in state order
+    pub fn cross_section(&self, year: u32) -> Vec<&CleanRecord> {
+        (0..self.states.len())
+            .filter_map(|id| self.index.get(&(id, year)))
+            .map(|&i| &self.records[i])
+            .collect()
+    }
+
+    // Balanced when every state has a record for every year in the panel
+    pub fn is_balanced(&self) -> bool {
+        self.records.len() == self.states.len() * self.years.len()
+    }
+
+    // (state, year) cells absent from an otherwise balanced panel
+    pub fn missing_cells(&self) -> Vec<(String, u32)> {
+        let mut missing = Vec::new();
+        for (id, state) in self.states.iter().enumerate() {
+            for &year in &self.years {
+                if !self.index.contains_key(&(id, year)) {
+                    missing.push((state.clone(), year));
+                }
+            }
+        }
+        missing
+    }
+
+    pub fn iter_series(&self) -> impl Iterator<Item = (&str, &[CleanRecord])> + '_ {
+        self.states
+            .iter()
+            .zip(&self.series)
+            .map(move |(state, range)| (state.as_str(), &self.records[range.clone()]))
+    }
+
+    pub fn iter_cross_sections(&self) -> impl Iterator<Item = (u32, Vec<&CleanRecord>)> + '_ {
+        self.years.iter().map(move |&year| (year, self.cross_section(year)))
+    }
+}
+
+impl Deref for Panel {
+    type Target = [CleanRecord];
+
+    fn deref(&self) -> &[CleanRecord] {
+        &self.records
+    }
+}
+
+impl From<Vec<CleanRecord>> for Panel {
+    fn from(records: Vec<CleanRecord>) -> Self {
+        Panel::new(records)
+    }
+}
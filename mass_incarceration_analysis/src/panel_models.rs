@@ -0,0 +1,318 @@
+use crate::data_processing::CleanRecord;
+use crate::ols::{build_design_matrix, inverse, solve, DesignMatrix, OlsSpec};
+use ndarray::{s, Array1, Array2, Axis};
+use serde::Serialize;
+use statrs::distribution::{ChiSquared, ContinuousCDF, StudentsT};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PanelEstimator {
+    Within,        // State fixed effects
+    TwoWay,        // State and year fixed effects
+    RandomEffects, // Swamy-Arora random state effects
+}
+
+// Inference uses standard errors clustered by state with the usual
+// G / (G - 1) * (n - 1) / (n - k) correction and t(G - 1) critical values
+#[derive(Debug, Clone, Serialize)]
+pub struct PanelCoefficient {
+    pub name: String,
+    pub estimate: f64,
+    pub std_error: f64, // Conventional, assumes iid errors
+    pub cluster_se: f64,
+    pub t_value: f64,
+    pub p_value: f64,
+    pub ci: (f64, f64),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PanelFit {
+    pub estimator: PanelEstimator,
+    pub response: String,
+    pub n: usize,
+    pub states: usize,
+    pub years: usize,
+    pub df_resid: usize,
+    pub coefficients: Vec<PanelCoefficient>, // Year effects of TwoWay are not listed
+    pub r_squared: f64,                      // Of the transformed regression; within R^2 for FE
+    pub sigma_e: f64,                        // Idiosyncratic error SD
+    pub sigma_u: Option<f64>,                // State effect SD, RandomEffects only
+    pub theta: Option<(f64, f64)>,           // Min and max quasi-demeaning weight, RandomEffects only
+    #[serde(skip)]
+    covariance: Array2<f64>, // Conventional covariance of the listed coefficients
+}
+
+impl PanelFit {
+    pub fn coefficient(&self, name: &str) -> Option<&PanelCoefficient> {
+        self.coefficients.iter().find(|c| c.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HausmanTest {
+    pub statistic: f64,
+    pub df: usize,
+    pub p_value: f64, // Small p: the state effects correlate with the regressors, use fixed effects
+}
+
+// Row indices of each state and each year in the design matrix
+fn groups(design: &DesignMatrix) -> (Vec<Vec<usize>>, Vec<u32>) {
+    let mut states: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, (state, _)) in design.rows.iter().enumerate() {
+        states.entry(state.as_str()).or_default().push(i);
+    }
+    let mut years: Vec<u32> = design.rows.iter().map(|(_, year)| *year).collect();
+    years.sort();
+    years.dedup();
+    (states.into_values().collect(), years)
+}
+
+fn group_means(m: &Array2<f64>, groups: &[Vec<usize>]) -> Array2<f64> {
+    let mut means = Array2::zeros((groups.len(), m.ncols()));
+    for (g, rows) in groups.iter().enumerate() {
+        let mean = m.select(Axis(0), rows).mean_axis(Axis(0)).unwrap_or_else(|| Array1::zeros(m.ncols()));
+        means.row_mut(g).assign(&mean);
+    }
+    means
+}
+
+// m_it - weight_g * mean_g(m), in place; weight 1 is the within transformation
+fn quasi_demean(m: &mut Array2<f64>, groups: &[Vec<usize>], weights: &[f64]) {
+    let means = group_means(m, groups);
+    for (g, rows) in groups.iter().enumerate() {
+        for &i in rows {
+            let shifted = &m.row(i) - &(&means.row(g) * weights[g]);
+            m.row_mut(i).assign(&shifted);
+        }
+    }
+}
+
+fn as_column(y: &Array1<f64>) -> Array2<f64> {
+    y.clone().insert_axis(Axis(1))
+}
+
+// Sum over states of (X_g' u_g)(X_g' u_g)'
+fn cluster_meat(x: &Array2<f64>, residuals: &Array1<f64>, groups: &[Vec<usize>]) -> Array2<f64> {
+    let k = x.ncols();
+    let mut meat = Array2::zeros((k, k));
+    for rows in groups {
+        let score = x.select(Axis(0), rows).t().dot(&residuals.select(Axis(0), rows));
+        let outer = score.view().insert_axis(Axis(1)).dot(&score.view().insert_axis(Axis(0)));
+        meat += &outer;
+    }
+    meat
+}
+
+struct Transformed {
+    x: Array2<f64>,
+    y: Array1<f64>,
+    names: Vec<String>,
+    reported: usize, // Leading columns listed as coefficients
+    absorbed: usize, // Degrees of freedom used by the fixed effects
+}
+
+// Fit `spec.response` on `spec.regressors` with state effects. The spec's
+// intercept is ignored: fixed effects absorb it and random effects always
+// include one. Regressors constant within every state (region dummies) are
+// collinear with state fixed effects and make Within and TwoWay fail.
+pub fn fit_panel(records: &[CleanRecord], spec: &OlsSpec, estimator: PanelEstimator) -> Result<PanelFit, Box<dyn Error>> {
+    let mut base = spec.clone();
+    base.intercept = false;
+    let design = build_design_matrix(records, &base)?;
+    let (state_rows, years) = groups(&design);
+    let (n, k) = design.x.dim();
+    let g = state_rows.len();
+    if g < 2 {
+        return Err("Panel estimators need at least two states".into());
+    }
+
+    let mut sigma_u = None;
+    let mut theta = None;
+    let transformed = match estimator {
+        PanelEstimator::Within | PanelEstimator::TwoWay => {
+            let mut x = design.x.clone();
+            let mut names = design.names.clone();
+            // Two-way effects as year dummies on the within-state scale, which
+            // stays exact when the panel is unbalanced
+            if estimator == PanelEstimator::TwoWay {
+                let mut dummies = Array2::zeros((n, years.len() - 1));
+                for (i, (_, year)) in design.rows.iter().enumerate() {
+                    if let Some(j) = years.iter().skip(1).position(|y| y == year) {
+                        dummies[[i, j]] = 1.0;
+                    }
+                }
+                x = ndarray::concatenate![Axis(1), x, dummies];
+                names.extend(years.iter().skip(1).map(|y| format!("year_{}", y)));
+            }
+            let mut y = as_column(&design.y);
+            let ones = vec![1.0; g];
+            quasi_demean(&mut x, &state_rows, &ones);
+            quasi_demean(&mut y, &state_rows, &ones);
+            Transformed { x, y: y.column(0).to_owned(), names, reported: k, absorbed: g }
+        }
+        PanelEstimator::RandomEffects => {
+            let (sigma2_e, sigma2_u) = variance_components(&design, &state_rows, spec)?;
+            let weights: Vec<f64> = state_rows
+                .iter()
+                .map(|rows| 1.0 - (sigma2_e / (rows.len() as f64 * sigma2_u + sigma2_e)).sqrt())
+                .collect();
+            let mut x = ndarray::concatenate![Axis(1), Array2::ones((n, 1)), design.x];
+            let mut y = as_column(&design.y);
+            quasi_demean(&mut x, &state_rows, &weights);
+            quasi_demean(&mut y, &state_rows, &weights);
+
+            sigma_u = Some(sigma2_u.sqrt());
+            let min = weights.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            theta = Some((min, max));
+            let mut names = vec!["intercept".to_string()];
+            names.extend(design.names.iter().cloned());
+            Transformed { x, y: y.column(0).to_owned(), names, reported: k + 1, absorbed: 0 }
+        }
+    };
+
+    let Transformed { x, y, names, reported, absorbed } = transformed;
+    let columns = x.ncols();
+    if n <= columns + absorbed {
+        return Err("Too few state-years for the panel estimator".into());
+    }
+    let df_resid = n - columns - absorbed;
+
+    let (beta, xtx_inv) = solve(&x, &y, spec.solver)?;
+    let residuals = &y - &x.dot(&beta);
+    let rss = residuals.dot(&residuals);
+    let y_mean = y.mean().unwrap_or(0.0);
+    let tss = y.mapv(|v| (v - y_mean).powi(2)).sum();
+    let sigma2 = rss / df_resid as f64;
+
+    let correction = g as f64 / (g - 1) as f64 * (n - 1) as f64 / (n - columns) as f64;
+    let cluster = xtx_inv.dot(&cluster_meat(&x, &residuals, &state_rows)).dot(&xtx_inv) * correction;
+    let t_dist = StudentsT::new(0.0, 1.0, (g - 1) as f64)?;
+    let t_crit = t_dist.inverse_cdf(1.0 - (1.0 - CONFIDENCE_LEVEL) / 2.0);
+
+    let coefficients = names
+        .iter()
+        .take(reported)
+        .enumerate()
+        .map(|(j, name)| {
+            let estimate = beta[j];
+            let cluster_se = cluster[[j, j]].sqrt();
+            let t_value = estimate / cluster_se;
+            PanelCoefficient {
+                name: name.clone(),
+                estimate,
+                std_error: (sigma2 * xtx_inv[[j, j]]).sqrt(),
+                cluster_se,
+                t_value,
+                p_value: 2.0 * (1.0 - t_dist.cdf(t_value.abs())),
+                ci: (estimate - t_crit * cluster_se, estimate + t_crit * cluster_se),
+            }
+        })
+        .collect();
+
+    Ok(PanelFit {
+        estimator,
+        response: spec.response.clone(),
+        n,
+        states: g,
+        years: years.len(),
+        df_resid,
+        coefficients,
+        r_squared: if tss > 0.0 { 1.0 - rss / tss } else { 1.0 },
+        sigma_e: sigma2.sqrt(),
+        sigma_u,
+        theta,
+        covariance: xtx_inv.slice(s![..reported, ..reported]).to_owned() * sigma2,
+    })
+}
+
+// Swamy-Arora variance components: the idiosyncratic variance from the
+// within regression and the state-effect variance from the regression on
+// state means, using the harmonic mean of the years per state
+fn variance_components(design: &DesignMatrix, state_rows: &[Vec<usize>], spec: &OlsSpec) -> Result<(f64, f64), Box<dyn Error>> {
+    let (n, k) = design.x.dim();
+    let g = state_rows.len();
+    if g <= k + 1 {
+        return Err("Random effects need more states than regressors plus one".into());
+    }
+
+    let ones = vec![1.0; g];
+    let mut x_within = design.x.clone();
+    let mut y_within = as_column(&design.y);
+    quasi_demean(&mut x_within, state_rows, &ones);
+    quasi_demean(&mut y_within, state_rows, &ones);
+    let y_within = y_within.column(0).to_owned();
+    // Regressors constant within states drop out of the within regression
+    let varying: Vec<usize> = (0..k)
+        .filter(|&j| {
+            let scale = design.x.column(j).dot(&design.x.column(j)).max(1.0);
+            x_within.column(j).dot(&x_within.column(j)) > scale * 1e-12
+        })
+        .collect();
+    let x_within = x_within.select(Axis(1), &varying);
+    let e = if varying.is_empty() {
+        y_within
+    } else {
+        let (beta, _) = solve(&x_within, &y_within, spec.solver)?;
+        &y_within - &x_within.dot(&beta)
+    };
+    let sigma2_e = e.dot(&e) / (n - g - varying.len()) as f64;
+
+    let x_between = ndarray::concatenate![Axis(1), Array2::ones((g, 1)), group_means(&design.x, state_rows)];
+    let y_between = group_means(&as_column(&design.y), state_rows).column(0).to_owned();
+    let (beta, _) = solve(&x_between, &y_between, spec.solver)?;
+    let u = &y_between - &x_between.dot(&beta);
+    let sigma2_between = u.dot(&u) / (g - k - 1) as f64;
+
+    let harmonic_years = g as f64 / state_rows.iter().map(|rows| 1.0 / rows.len() as f64).sum::<f64>();
+    Ok((sigma2_e, (sigma2_between - sigma2_e / harmonic_years).max(0.0)))
+}
+
+// Hausman test of fixed against random effects on the slopes both fits share,
+// using their conventional covariances
+pub fn hausman_test(fixed: &PanelFit, random: &PanelFit) -> Result<HausmanTest, Box<dyn Error>> {
+    let shared: Vec<(usize, usize)> = fixed
+        .coefficients
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| random.coefficients.iter().position(|r| r.name == c.name).map(|j| (i, j)))
+        .collect();
+    if shared.is_empty() {
+        return Err("The fits share no coefficients".into());
+    }
+
+    let m = shared.len();
+    let difference = Array1::from_iter(
+        shared.iter().map(|&(i, j)| fixed.coefficients[i].estimate - random.coefficients[j].estimate),
+    );
+    let variance = Array2::from_shape_fn((m, m), |(a, b)| {
+        let (fa, ra) = shared[a];
+        let (fb, rb) = shared[b];
+        fixed.covariance[[fa, fb]] - random.covariance[[ra, rb]]
+    });
+    let statistic = difference.dot(&inverse(&variance)?.dot(&difference));
+    let chi2 = ChiSquared::new(m as f64)?;
+    Ok(HausmanTest { statistic, df: m, p_value: 1.0 - chi2.cdf(statistic.max(0.0)) })
+}
+
+pub fn print_panel_fit(fit: &PanelFit) {
+    println!(
+        "\n--- {:?}: {} (n = {}, {} states, {} years) ---",
+        fit.estimator, fit.response, fit.n, fit.states, fit.years
+    );
+    println!("{:<24} {:>12} {:>10} {:>12} {:>8} {:>8}", "Term", "Estimate", "SE", "Cluster SE", "t", "p");
+    for c in &fit.coefficients {
+        println!(
+            "{:<24} {:>12.4} {:>10.4} {:>12.4} {:>8.2} {:>8.4}",
+            c.name, c.estimate, c.std_error, c.cluster_se, c.t_value, c.p_value
+        );
+    }
+    print!("R-squared: {:.4} | sigma_e: {:.4}", fit.r_squared, fit.sigma_e);
+    if let (Some(sigma_u), Some((min, max))) = (fit.sigma_u, fit.theta) {
+        print!(" | sigma_u: {:.4} | theta: {:.3}-{:.3}", sigma_u, min, max);
+    }
+    println!();
+}
@@ -16,6 +16,9 @@ pub fn visualize_similarity_graph(graph: &UnGraph<String, f32>, filename: &str)
 
     Ok(())
 }
+// Minimum calculate_similarity score for an edge
+pub const SIMILARITY_CUTOFF: f32 = 0.7;
+
 pub fn construct_similarity_graph(records: &[CleanRecord]) -> UnGraph<String, f32> {
     let mut graph = UnGraph::new_undirected();
 
@@ -28,7 +31,7 @@ pub fn construct_similarity_graph(records: &[CleanRecord]) -> UnGraph<String, f3
     for i in 0..records.len() {
         for j in (i + 1)..records.len() {
             let sim = calculate_similarity(&records[i], &records[j]);
-            if sim > 0.7 {
+            if sim > SIMILARITY_CUTOFF {
                 graph.add_edge(nodes[i], nodes[j], sim); // Use NodeIndex
             }
         }
@@ -0,0 +1,280 @@
+use crate::data_processing::{normalize_jurisdiction, CleanRecord};
+use crate::rates::{Denominator, RateScale, RateSpec};
+use crate::states::{FEDERAL, STATES};
+use polars::prelude::*;
+use std::collections::BTreeSet;
+
+// Attached covariates become one column each, e.g. covariate_unemployment
+const COVARIATE_PREFIX: &str = "covariate_";
+
+// Count columns of the combined CSV that get a rate column
+const COUNT_COLUMNS: [(&str, &str); 10] = [
+    ("violent_crime_total", "crime_rate"),
+    ("murder_manslaughter", "murder_manslaughter_rate"),
+    ("rape_legacy", "rape_legacy_rate"),
+    ("rape_revised", "rape_revised_rate"),
+    ("robbery", "robbery_rate"),
+    ("agg_assault", "agg_assault_rate"),
+    ("property_crime_total", "property_crime_rate"),
+    ("burglary", "burglary_rate"),
+    ("larceny", "larceny_rate"),
+    ("vehicle_theft", "vehicle_theft_rate"),
+];
+
+// count / state_population on the given scale
+fn population_rate(count: &str, scale: RateScale) -> Expr {
+    (col(count).cast(DataType::Float64) / col("state_population").cast(DataType::Float64)) * lit(scale.multiplier() as f64)
+}
+
+// Lazily scan crime_and_incarceration_by_state.csv (or any file with its columns)
+pub fn scan_combined(file_path: &str) -> PolarsResult<LazyFrame> {
+    LazyCsvReader::new(file_path)
+        .with_has_header(true)
+        .with_infer_schema_length(Some(10_000))
+        .finish()
+}
+
+// Drop FEDERAL and rows without population or crime data, then add the rate
+// columns that apply_rate_spec computes for CleanRecord. The combined CSV only
+// has total population, so other denominators are an error.
+pub fn with_rates(lf: LazyFrame, spec: &RateSpec) -> PolarsResult<LazyFrame> {
+    if spec.denominator != Denominator::TotalPopulation {
+        return Err(PolarsError::ComputeError(
+            format!("the combined CSV has no column for rates {}", spec.label()).into(),
+        ));
+    }
+    let mut rates = vec![population_rate("prisoner_count", spec.scale).alias("incarceration_rate")];
+    rates.extend(COUNT_COLUMNS.iter().map(|(count, rate)| population_rate(count, spec.scale).alias(rate)));
+    rates.push(lit(spec.label()).alias("rate_spec"));
+
+    Ok(lf.filter(
+        col("jurisdiction")
+            .neq(lit(FEDERAL))
+            .and(col("state_population").is_not_null())
+            .and(col("state_population").gt(lit(0)))
+            .and(col("violent_crime_total").is_not_null()),
+    )
+    .with_columns(rates))
+}
+
+// Census region and division for every state in the registry
+pub fn region_table() -> PolarsResult<DataFrame> {
+    let names: Vec<&str> = STATES.iter().map(|s| s.name).collect();
+    let regions: Vec<Option<String>> = STATES.iter().map(|s| s.region().map(|r| format!("{:?}", r))).collect();
+    let divisions: Vec<Option<String>> = STATES.iter().map(|s| s.division.map(|d| format!("{:?}", d))).collect();
+    let fips: Vec<u32> = STATES.iter().map(|s| s.fips as u32).collect();
+
+    DataFrame::new(vec![
+        Series::new("jurisdiction", names),
+        Series::new("fips", fips),
+        Series::new("region", regions),
+        Series::new("division", divisions),
+    ])
+}
+
+pub fn with_regions(lf: LazyFrame) -> PolarsResult<LazyFrame> {
+    Ok(lf.left_join(region_table()?.lazy(), col("jurisdiction"), col("jurisdiction")))
+}
+
+fn averages_by(lf: LazyFrame, keys: Vec<Expr>) -> LazyFrame {
+    lf.group_by(keys.clone())
+        .agg([
+            col("incarceration_rate").mean().alias("avg_incarceration_rate"),
+            col("crime_rate").mean().alias("avg_crime_rate"),
+            col("prisoner_count").sum().alias("total_prisoners"),
+            col("violent_crime_total").sum().alias("total_violent_crime"),
+            col("state_population").sum().alias("total_population"),
+            col("jurisdiction").count().alias("states"),
+        ])
+        .sort_by_exprs(keys, SortMultipleOptions::default())
+}
+
+// Same numbers as plot_national_averages, plus summed counts
+pub fn yearly_averages(lf: LazyFrame) -> LazyFrame {
+    averages_by(lf, vec![col("year")])
+}
+
+pub fn region_averages(lf: LazyFrame) -> PolarsResult<LazyFrame> {
+    Ok(averages_by(with_regions(lf)?, vec![col("region"), col("year")]))
+}
+
+pub fn records_to_frame(records: &[CleanRecord]) -> PolarsResult<DataFrame> {
+    let u32_col = |name: &str, get: fn(&CleanRecord) -> u32| Series::new(name, records.iter().map(get).collect::<Vec<u32>>());
+    let opt_col = |name: &str, get: fn(&CleanRecord) -> Option<u32>| {
+        Series::new(name, records.iter().map(get).collect::<Vec<Option<u32>>>())
+    };
+    let f32_col = |name: &str, get: fn(&CleanRecord) -> f32| Series::new(name, records.iter().map(get).collect::<Vec<f32>>());
+    let opt_f32_col = |name: &str, get: fn(&CleanRecord) -> Option<f32>| {
+        Series::new(name, records.iter().map(get).collect::<Vec<Option<f32>>>())
+    };
+    let bool_col = |name: &str, get: fn(&CleanRecord) -> bool| Series::new(name, records.iter().map(get).collect::<Vec<bool>>());
+
+    let mut columns = vec![
+        Series::new("jurisdiction", records.iter().map(|r| r.jurisdiction.as_str()).collect::<Vec<&str>>()),
+        bool_col("includes_jails", |r| r.includes_jails),
+        u32_col("year", |r| r.year),
+        u32_col("prisoner_count", |r| r.prisoner_count),
+        bool_col("crime_reporting_change", |r| r.crime_reporting_change),
+        bool_col("crimes_estimated", |r| r.crimes_estimated),
+        u32_col("state_population", |r| r.state_population),
+        u32_col("violent_crime_total", |r| r.violent_crime_total),
+        u32_col("murder_manslaughter", |r| r.murder_manslaughter),
+        opt_col("rape_legacy", |r| r.rape_legacy),
+        opt_col("rape_revised", |r| r.rape_revised),
+        u32_col("robbery", |r| r.robbery),
+        u32_col("agg_assault", |r| r.agg_assault),
+        u32_col("property_crime_total", |r| r.property_crime_total),
+        u32_col("burglary", |r| r.burglary),
+        u32_col("larceny", |r| r.larceny),
+        u32_col("vehicle_theft", |r| r.vehicle_theft),
+        f32_col("incarceration_rate", |r| r.incarceration_rate),
+        f32_col("crime_rate", |r| r.crime_rate),
+        f32_col("murder_manslaughter_rate", |r| r.murder_manslaughter_rate),
+        opt_f32_col("rape_legacy_rate", |r| r.rape_legacy_rate),
+        opt_f32_col("rape_revised_rate", |r| r.rape_revised_rate),
+        f32_col("robbery_rate", |r| r.robbery_rate),
+        f32_col("agg_assault_rate", |r| r.agg_assault_rate),
+        f32_col("property_crime_rate", |r| r.property_crime_rate),
+        f32_col("burglary_rate", |r| r.burglary_rate),
+        f32_col("larceny_rate", |r| r.larceny_rate),
+        f32_col("vehicle_theft_rate", |r| r.vehicle_theft_rate),
+        Series::new("imputed", records.iter().map(|r| r.imputed.join(";")).collect::<Vec<String>>()),
+        Series::new("rate_spec", records.iter().map(|r| r.rate_spec.label()).collect::<Vec<String>>()),
+        Series::new("rate_denominator", records.iter().map(|r| r.denominator_covariate()).collect::<Vec<Option<f64>>>()),
+    ];
+    let covariates: BTreeSet<&str> = records.iter().flat_map(|r| r.covariates.keys().map(String::as_str)).collect();
+    for name in covariates {
+        let values: Vec<Option<f64>> = records.iter().map(|r| r.covariate(name)).collect();
+        columns.push(Series::new(&format!("{}{}", COVARIATE_PREFIX, name), values));
+    }
+    DataFrame::new(columns)
+}
+
+fn u32_values(df: &DataFrame, name: &str) -> PolarsResult<Vec<Option<u32>>> {
+    Ok(df.column(name)?.cast(&DataType::UInt32)?.u32()?.into_iter().collect())
+}
+
+fn bool_values(df: &DataFrame, name: &str) -> PolarsResult<Vec<bool>> {
+    match df.column(name) {
+        // The CSV writes "True"/"False", which may be read as text
+        Ok(column) if column.dtype() == &DataType::String => Ok(column
+            .str()?
+            .into_iter()
+            .map(|v| matches!(v.map(|s| s.to_lowercase()).as_deref(), Some("true") | Some("1")))
+            .collect()),
+        Ok(column) => Ok(column.cast(&DataType::Boolean)?.bool()?.into_iter().map(|v| v.unwrap_or(false)).collect()),
+        Err(_) => Ok(vec![false; df.height()]),
+    }
+}
+
+// A ';'-joined list column such as `imputed`; empty when the column is absent
+fn list_values(df: &DataFrame, name: &str) -> PolarsResult<Vec<Vec<String>>> {
+    let Ok(column) = df.column(name) else {
+        return Ok(vec![Vec::new(); df.height()]);
+    };
+    Ok(column
+        .str()?
+        .into_iter()
+        .map(|v| v.unwrap_or_default().split(';').filter(|f| !f.is_empty()).map(String::from).collect())
+        .collect())
+}
+
+// Rate spec of every row: the `rate_spec` label column when present, with the
+// stored covariate denominator, or the default spec
+fn rate_specs(df: &DataFrame) -> PolarsResult<Vec<(RateSpec, Option<f64>)>> {
+    let Ok(labels) = df.column("rate_spec") else {
+        return Ok(vec![(RateSpec::default(), None); df.height()]);
+    };
+    let denominators: Vec<Option<f64>> = match df.column("rate_denominator") {
+        Ok(c) => c.cast(&DataType::Float64)?.f64()?.into_iter().collect(),
+        Err(_) => vec![None; df.height()],
+    };
+    labels
+        .str()?
+        .into_iter()
+        .zip(denominators)
+        .map(|(label, denominator)| {
+            let label = label.unwrap_or_default();
+            let spec = RateSpec::from_label(label)
+                .ok_or_else(|| PolarsError::ComputeError(format!("unknown rate spec '{}'", label).into()))?;
+            Ok((spec, denominator))
+        })
+        .collect()
+}
+
+// Rebuild CleanRecords from a frame with the combined CSV's count columns.
+// Rates are recomputed from the counts under the stored rate spec; rows
+// without a population are skipped.
+pub fn frame_to_records(df: &DataFrame) -> PolarsResult<Vec<CleanRecord>> {
+    let jurisdictions: Vec<Option<&str>> = df.column("jurisdiction")?.str()?.into_iter().collect();
+    let column = |name: &str| -> PolarsResult<Vec<Option<u32>>> {
+        match df.column(name) {
+            Ok(_) => u32_values(df, name),
+            Err(_) => Ok(vec![None; df.height()]),
+        }
+    };
+    let years = u32_values(df, "year")?;
+    let prisoners = column("prisoner_count")?;
+    let population = column("state_population")?;
+    let violent = column("violent_crime_total")?;
+    let murder = column("murder_manslaughter")?;
+    let rape_legacy = column("rape_legacy")?;
+    let rape_revised = column("rape_revised")?;
+    let robbery = column("robbery")?;
+    let agg_assault = column("agg_assault")?;
+    let property = column("property_crime_total")?;
+    let burglary = column("burglary")?;
+    let larceny = column("larceny")?;
+    let vehicle_theft = column("vehicle_theft")?;
+    let includes_jails = bool_values(df, "includes_jails")?;
+    let reporting_change = bool_values(df, "crime_reporting_change")?;
+    let estimated = bool_values(df, "crimes_estimated")?;
+    let specs = rate_specs(df)?;
+    let imputed = list_values(df, "imputed")?;
+    let warnings = list_values(df, "validation_warnings")?;
+    let mut covariates = Vec::new();
+    for column in df.get_columns() {
+        if let Some(name) = column.name().strip_prefix(COVARIATE_PREFIX) {
+            let values: Vec<Option<f64>> = column.cast(&DataType::Float64)?.f64()?.into_iter().collect();
+            covariates.push((name.to_string(), values));
+        }
+    }
+
+    let mut records = Vec::new();
+    for i in 0..df.height() {
+        let (Some(jurisdiction), Some(year), Some(state_population)) = (jurisdictions[i], years[i], population[i]) else {
+            continue;
+        };
+        if state_population == 0 {
+            continue;
+        }
+        let mut c = CleanRecord {
+            jurisdiction: normalize_jurisdiction(jurisdiction),
+            year,
+            prisoner_count: prisoners[i].unwrap_or(0),
+            state_population,
+            violent_crime_total: violent[i].unwrap_or(0),
+            murder_manslaughter: murder[i].unwrap_or(0),
+            rape_legacy: rape_legacy[i],
+            rape_revised: rape_revised[i],
+            robbery: robbery[i].unwrap_or(0),
+            agg_assault: agg_assault[i].unwrap_or(0),
+            property_crime_total: property[i].unwrap_or(0),
+            burglary: burglary[i].unwrap_or(0),
+            larceny: larceny[i].unwrap_or(0),
+            vehicle_theft: vehicle_theft[i].unwrap_or(0),
+            includes_jails: includes_jails[i],
+            crime_reporting_change: reporting_change[i],
+            crimes_estimated: estimated[i],
+            imputed: imputed[i].clone(),
+            covariates: covariates.iter().filter_map(|(name, values)| Some((name.clone(), values[i]?))).collect(),
+            validation_warnings: warnings[i].clone(),
+            ..Default::default()
+        };
+        let (spec, denominator) = specs[i].clone();
+        c.restore_rate_spec(spec, denominator);
+        records.push(c);
+    }
+
+    Ok(records)
+}
@@ -0,0 +1,144 @@
+use crate::data_processing::CleanRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateScale {
+    PerThousand,
+    PerTenThousand,
+    #[default]
+    PerHundredThousand,
+}
+
+impl RateScale {
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            RateScale::PerThousand => 1_000.0,
+            RateScale::PerTenThousand => 10_000.0,
+            RateScale::PerHundredThousand => 100_000.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Denominator {
+    #[default]
+    TotalPopulation,
+    AdultPopulation,    // The "adult_population" covariate
+    Covariate(String),  // Any population column attached from a covariate file
+}
+
+impl Denominator {
+    // Covariate holding the denominator; None for total population
+    pub fn covariate_name(&self) -> Option<&str> {
+        match self {
+            Denominator::TotalPopulation => None,
+            Denominator::AdultPopulation => Some("adult_population"),
+            Denominator::Covariate(name) => Some(name.as_str()),
+        }
+    }
+}
+
+// How every rate on a CleanRecord is defined: count / denominator * scale.
+// The default is per 100k total residents.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RateSpec {
+    pub scale: RateScale,
+    pub denominator: Denominator,
+}
+
+impl RateSpec {
+    pub fn new(scale: RateScale, denominator: Denominator) -> Self {
+        RateSpec { scale, denominator }
+    }
+
+    // e.g. "per 100k total population", for plot labels and manifests
+    pub fn label(&self) -> String {
+        let scale = match self.scale {
+            RateScale::PerThousand => "1k",
+            RateScale::PerTenThousand => "10k",
+            RateScale::PerHundredThousand => "100k",
+        };
+        let denominator = match &self.denominator {
+            Denominator::TotalPopulation => "total population",
+            Denominator::AdultPopulation => "adult population",
+            Denominator::Covariate(name) => name.as_str(),
+        };
+        format!("per {} {}", scale, denominator)
+    }
+
+    // Inverse of label(), for specs stored next to exported data
+    pub fn from_label(label: &str) -> Option<Self> {
+        let (scale, denominator) = label.strip_prefix("per ")?.split_once(' ')?;
+        let scale = match scale {
+            "1k" => RateScale::PerThousand,
+            "10k" => RateScale::PerTenThousand,
+            "100k" => RateScale::PerHundredThousand,
+            _ => return None,
+        };
+        let denominator = match denominator {
+            "total population" => Denominator::TotalPopulation,
+            "adult population" => Denominator::AdultPopulation,
+            "" => return None,
+            name => Denominator::Covariate(name.to_string()),
+        };
+        Some(RateSpec { scale, denominator })
+    }
+
+    // count / denominator on this spec's scale; NaN without a positive denominator
+    pub fn rate(&self, count: f64, denominator: f64) -> f32 {
+        if denominator > 0.0 {
+            (count / denominator * self.scale.multiplier() as f64) as f32
+        } else {
+            f32::NAN
+        }
+    }
+}
+
+impl CleanRecord {
+    // Denominator of the record's rate spec, if the record has it
+    pub fn rate_denominator(&self) -> Option<f32> {
+        let value = match self.rate_spec.denominator.covariate_name() {
+            None => Some(self.state_population as f32),
+            Some(name) => self.covariate(name).map(|v| v as f32),
+        };
+        value.filter(|v| *v > 0.0)
+    }
+
+    // Value of a covariate denominator, stored with exports so the spec can be restored
+    pub fn denominator_covariate(&self) -> Option<f64> {
+        self.covariate(self.rate_spec.denominator.covariate_name()?)
+    }
+
+    // Reattach a spec read back from storage along with the stored covariate
+    // denominator, then recompute the rates from the counts
+    pub fn restore_rate_spec(&mut self, spec: RateSpec, denominator: Option<f64>) {
+        if let (Some(name), Some(value)) = (spec.denominator.covariate_name(), denominator) {
+            self.covariates.insert(name.to_string(), value);
+        }
+        self.rate_spec = spec;
+        self.compute_rates();
+    }
+
+    // A count as a rate under the record's spec; NaN without a denominator
+    pub fn rate_of(&self, count: u32) -> f32 {
+        match self.rate_denominator() {
+            Some(denominator) => count as f32 / denominator * self.rate_spec.scale.multiplier(),
+            None => f32::NAN,
+        }
+    }
+}
+
+// Recompute every rate under `spec`. Records missing the denominator are
+// dropped. Apply before with_crime_measure / with_covariate, which overwrite
+// crime_rate; imputed records already carry the spec of the row they copy.
+pub fn apply_rate_spec(records: &[CleanRecord], spec: &RateSpec) -> Vec<CleanRecord> {
+    records
+        .iter()
+        .filter_map(|r| {
+            let mut c = r.clone();
+            c.rate_spec = spec.clone();
+            c.rate_denominator()?;
+            c.compute_rates();
+            Some(c)
+        })
+        .collect()
+}
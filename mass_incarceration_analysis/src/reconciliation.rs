@@ -0,0 +1,224 @@
+use crate::data_processing::{normalize_jurisdiction, DirtyRecord};
+use crate::sources::{load_prison_custody, load_ucr, parse_flag, CustodyRecord, UcrRecord};
+use csv::{Reader, Writer};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+// One value that differs between an upstream source and the combined file.
+// A value present on one side only has no differences.
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    pub jurisdiction: String,
+    pub year: u32,
+    pub source: String, // "custody" or "ucr"
+    pub field: String,
+    pub source_value: Option<f64>,
+    pub combined_value: Option<f64>,
+    pub abs_diff: Option<f64>,
+    pub rel_diff: Option<f64>, // abs_diff / |source_value|
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FieldSummary {
+    pub source: String,
+    pub field: String,
+    pub compared: usize,
+    pub mismatches: usize,
+    pub missing_on_one_side: usize,
+    pub max_abs_diff: f64,
+    pub max_rel_diff: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct ReconciliationReport {
+    pub mismatches: Vec<Mismatch>,
+    pub summary: Vec<FieldSummary>,
+    pub only_in_custody: Vec<(String, u32)>,  // State-years the combined file lacks
+    pub only_in_ucr: Vec<(String, u32)>,
+    pub only_in_combined: Vec<(String, u32)>, // Combined rows neither source has
+}
+
+// Combined-file value of a count ("4468912.0") or flag ("True") column
+fn combined_value(raw: &str) -> Option<f64> {
+    let trimmed = raw.trim();
+    match trimmed.to_lowercase().as_str() {
+        "" => None,
+        "true" | "false" => Some(if parse_flag(trimmed) { 1.0 } else { 0.0 }),
+        _ => trimmed.replace(",", "").parse::<f64>().ok(),
+    }
+}
+
+fn flag_value(flag: bool) -> Option<f64> {
+    Some(if flag { 1.0 } else { 0.0 })
+}
+
+fn count_value(count: Option<u32>) -> Option<f64> {
+    count.map(|n| n as f64)
+}
+
+fn custody_fields(c: &CustodyRecord, d: &DirtyRecord) -> Vec<(&'static str, Option<f64>, Option<f64>)> {
+    vec![
+        ("prisoner_count", Some(c.prisoner_count as f64), combined_value(&d.prisoner_count)),
+        ("includes_jails", flag_value(c.includes_jails), combined_value(&d.includes_jails)),
+    ]
+}
+
+fn ucr_fields(u: &UcrRecord, d: &DirtyRecord) -> Vec<(&'static str, Option<f64>, Option<f64>)> {
+    vec![
+        ("crime_reporting_change", flag_value(u.crime_reporting_change), combined_value(&d.crime_reporting_change)),
+        ("crimes_estimated", flag_value(u.crimes_estimated), combined_value(&d.crimes_estimated)),
+        ("state_population", count_value(u.state_population), combined_value(&d.state_population)),
+        ("violent_crime_total", count_value(u.violent_crime_total), combined_value(&d.violent_crime_total)),
+        ("murder_manslaughter", count_value(u.murder_manslaughter), combined_value(&d.murder_manslaughter)),
+        ("rape_legacy", count_value(u.rape_legacy), combined_value(&d.rape_legacy)),
+        ("rape_revised", count_value(u.rape_revised), combined_value(&d.rape_revised)),
+        ("robbery", count_value(u.robbery), combined_value(&d.robbery)),
+        ("agg_assault", count_value(u.agg_assault), combined_value(&d.agg_assault)),
+        ("property_crime_total", count_value(u.property_crime_total), combined_value(&d.property_crime_total)),
+        ("burglary", count_value(u.burglary), combined_value(&d.burglary)),
+        ("larceny", count_value(u.larceny), combined_value(&d.larceny)),
+        ("vehicle_theft", count_value(u.vehicle_theft), combined_value(&d.vehicle_theft)),
+    ]
+}
+
+// Compare every overlapping state-year value of the custody and UCR records
+// with the combined rows. Differences at or below `tolerance` are ignored.
+pub fn reconcile(
+    custody: &[CustodyRecord],
+    ucr: &[UcrRecord],
+    combined: &[DirtyRecord],
+    tolerance: f64,
+) -> ReconciliationReport {
+    let combined_index: HashMap<(String, u32), &DirtyRecord> = combined
+        .iter()
+        .filter_map(|d| {
+            let year = d.year.trim().parse::<u32>().ok()?;
+            Some(((normalize_jurisdiction(&d.jurisdiction), year), d))
+        })
+        .collect();
+
+    let mut report = ReconciliationReport::default();
+    let mut summary: BTreeMap<(&str, &str), FieldSummary> = BTreeMap::new();
+    let mut seen: HashMap<(String, u32), bool> = combined_index.keys().map(|k| (k.clone(), false)).collect();
+
+    let mut compare = |source: &'static str, key: &(String, u32), fields: Vec<(&'static str, Option<f64>, Option<f64>)>| {
+        for (field, source_value, combined_value) in fields {
+            let entry = summary.entry((source, field)).or_insert_with(|| FieldSummary {
+                source: source.to_string(),
+                field: field.to_string(),
+                ..Default::default()
+            });
+            if source_value.is_none() && combined_value.is_none() {
+                continue;
+            }
+            entry.compared += 1;
+
+            let (abs_diff, rel_diff) = match (source_value, combined_value) {
+                (Some(a), Some(b)) => {
+                    let diff = (b - a).abs();
+                    if diff <= tolerance {
+                        continue;
+                    }
+                    let rel = if a != 0.0 { Some(diff / a.abs()) } else { None };
+                    entry.max_abs_diff = entry.max_abs_diff.max(diff);
+                    entry.max_rel_diff = entry.max_rel_diff.max(rel.unwrap_or(0.0));
+                    (Some(diff), rel)
+                }
+                _ => {
+                    entry.missing_on_one_side += 1;
+                    (None, None)
+                }
+            };
+            entry.mismatches += 1;
+
+            report.mismatches.push(Mismatch {
+                jurisdiction: key.0.clone(),
+                year: key.1,
+                source: source.to_string(),
+                field: field.to_string(),
+                source_value,
+                combined_value,
+                abs_diff,
+                rel_diff,
+            });
+        }
+    };
+
+    for c in custody {
+        let key = (normalize_jurisdiction(&c.jurisdiction), c.year);
+        match combined_index.get(&key) {
+            Some(d) => {
+                seen.insert(key.clone(), true);
+                compare("custody", &key, custody_fields(c, d));
+            }
+            None => report.only_in_custody.push(key),
+        }
+    }
+
+    for u in ucr {
+        let key = (normalize_jurisdiction(&u.jurisdiction), u.year);
+        match combined_index.get(&key) {
+            Some(d) => {
+                seen.insert(key.clone(), true);
+                compare("ucr", &key, ucr_fields(u, d));
+            }
+            // The combined file only has states with a custody count
+            None => report.only_in_ucr.push(key),
+        }
+    }
+
+    report.only_in_combined = seen.into_iter().filter(|(_, matched)| !matched).map(|(key, _)| key).collect();
+    report.only_in_combined.sort();
+    report.summary = summary.into_values().collect();
+    report
+}
+
+// Load the three files and reconcile them
+pub fn reconcile_files(
+    custody_path: &str,
+    ucr_path: &str,
+    combined_path: &str,
+    tolerance: f64,
+) -> Result<ReconciliationReport, Box<dyn Error>> {
+    let custody = load_prison_custody(custody_path)?;
+    let ucr = load_ucr(ucr_path)?;
+
+    let mut rdr = Reader::from_path(combined_path)?;
+    let mut combined = Vec::new();
+    for result in rdr.deserialize() {
+        let record: DirtyRecord = result?;
+        combined.push(record);
+    }
+
+    Ok(reconcile(&custody, &ucr, &combined, tolerance))
+}
+
+pub fn write_mismatches_csv(mismatches: &[Mismatch], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_path(output_path)?;
+    for mismatch in mismatches {
+        wtr.serialize(mismatch)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn print_reconciliation_summary(report: &ReconciliationReport) {
+    println!("\n--- Reconciliation ---");
+    println!(
+        "{:<8} {:<24} {:>9} {:>11} {:>9} {:>14} {:>13}",
+        "Source", "Field", "Compared", "Mismatches", "One-side", "Max abs diff", "Max rel diff"
+    );
+    for s in &report.summary {
+        println!(
+            "{:<8} {:<24} {:>9} {:>11} {:>9} {:>14.1} {:>13.4}",
+            s.source, s.field, s.compared, s.mismatches, s.missing_on_one_side, s.max_abs_diff, s.max_rel_diff
+        );
+    }
+    println!(
+        "State-years only in custody: {} | only in UCR: {} | only in combined: {}",
+        report.only_in_custody.len(),
+        report.only_in_ucr.len(),
+        report.only_in_combined.len()
+    );
+}
@@ -0,0 +1,259 @@
+use crate::data_processing::{normalize_jurisdiction, parse_thousands, DirtyRecord};
+use crate::states::resolve_jurisdiction;
+use csv::{ReaderBuilder, Writer};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+// One (jurisdiction, year) row of the wide prison custody table
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustodyRecord {
+    pub jurisdiction: String,
+    pub year: u32,
+    pub prisoner_count: u32,
+    pub includes_jails: bool,
+}
+
+// One (state, year) row of ucr_by_state.csv; empty cells stay None
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UcrRecord {
+    pub jurisdiction: String,
+    pub year: u32,
+    pub crime_reporting_change: bool,
+    pub crimes_estimated: bool,
+    pub state_population: Option<u32>,
+    pub violent_crime_total: Option<u32>,
+    pub murder_manslaughter: Option<u32>,
+    pub rape_legacy: Option<u32>,
+    pub rape_revised: Option<u32>,
+    pub robbery: Option<u32>,
+    pub agg_assault: Option<u32>,
+    pub property_crime_total: Option<u32>,
+    pub burglary: Option<u32>,
+    pub larceny: Option<u32>,
+    pub vehicle_theft: Option<u32>,
+}
+
+// Result of joining custody and UCR rows on (state, year)
+#[derive(Debug, Default)]
+pub struct CombinedJoin {
+    pub rows: Vec<DirtyRecord>,
+    pub unmatched_custody: Vec<(String, u32)>, // Custody rows with no UCR data (e.g. Federal)
+    pub unmatched_ucr: Vec<(String, u32)>,     // UCR rows with no custody count (e.g. DC, 2017)
+}
+
+// Excel exports prefix the first header with a UTF-8 byte order mark
+pub(crate) fn strip_bom(field: &str) -> &str {
+    field.trim_start_matches('\u{feff}')
+}
+
+// The raw sources write flags as 0/1 while the combined file uses True/False
+pub(crate) fn parse_flag(field: &str) -> bool {
+    matches!(field.trim().to_lowercase().as_str(), "1" | "true")
+}
+
+// Reshape prison_custody_by_state.csv (one column per year) into long records
+pub fn load_prison_custody(file_path: &str) -> Result<Vec<CustodyRecord>, Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_path(file_path)?;
+
+    // Every header after jurisdiction/includes_jails that parses as a year is a data column
+    let headers = rdr.headers()?.clone();
+    let year_columns: Vec<(usize, u32)> = headers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, h)| strip_bom(h).trim().parse::<u32>().ok().map(|year| (i, year)))
+        .collect();
+
+    if year_columns.is_empty() {
+        return Err(format!("No year columns found in {}", file_path).into());
+    }
+
+    let mut records = Vec::new();
+    for result in rdr.records() {
+        let row = result?;
+        let raw_name = row.get(0).unwrap_or("").trim();
+        if raw_name.is_empty() {
+            continue; // Skip blank spacer rows
+        }
+        let jurisdiction = resolve_jurisdiction(raw_name)
+            .ok_or_else(|| format!("Unrecognized jurisdiction '{}' in {}", raw_name, file_path))?;
+        let includes_jails = parse_flag(row.get(1).unwrap_or(""));
+
+        for &(column, year) in &year_columns {
+            let raw = row.get(column).unwrap_or("");
+            if raw.trim().is_empty() {
+                continue; // Jurisdiction did not report that year
+            }
+            let prisoner_count = parse_thousands(raw).ok_or_else(|| {
+                format!("Invalid prisoner_count '{}' for {} {}", raw, jurisdiction, year)
+            })?;
+
+            records.push(CustodyRecord {
+                jurisdiction: jurisdiction.clone(),
+                year,
+                prisoner_count,
+                includes_jails,
+            });
+        }
+    }
+
+    Ok(records)
+}
+
+// Load ucr_by_state.csv, skipping the blank padding rows and trailing empty columns
+pub fn load_ucr(file_path: &str) -> Result<Vec<UcrRecord>, Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_path(file_path)?;
+
+    let headers = rdr.headers()?.clone();
+    let columns: HashMap<String, usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (strip_bom(h).trim().to_string(), i))
+        .filter(|(h, _)| !h.is_empty())
+        .collect();
+
+    let mut records = Vec::new();
+    for result in rdr.records() {
+        let row = result?;
+        let field = |name: &str| -> &str {
+            columns.get(name).and_then(|&i| row.get(i)).unwrap_or("").trim()
+        };
+        let count = |name: &str| -> Result<Option<u32>, String> {
+            let raw = field(name);
+            if raw.is_empty() {
+                return Ok(None);
+            }
+            parse_thousands(raw)
+                .map(Some)
+                .ok_or_else(|| format!("Invalid {} '{}' for {} {}", name, raw, field("jurisdiction"), field("year")))
+        };
+
+        let raw_name = field("jurisdiction");
+        if raw_name.is_empty() {
+            continue; // Blank padding row
+        }
+        let jurisdiction = resolve_jurisdiction(raw_name)
+            .ok_or_else(|| format!("Unrecognized jurisdiction '{}' in {}", raw_name, file_path))?;
+        let year = field("year")
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid year '{}' for {}", field("year"), jurisdiction))?;
+
+        records.push(UcrRecord {
+            jurisdiction,
+            year,
+            crime_reporting_change: parse_flag(field("crime_reporting_change")),
+            crimes_estimated: parse_flag(field("crimes_estimated")),
+            state_population: count("state_population")?,
+            violent_crime_total: count("violent_crime_total")?,
+            murder_manslaughter: count("murder_manslaughter")?,
+            rape_legacy: count("rape_legacy")?,
+            rape_revised: count("rape_revised")?,
+            robbery: count("robbery")?,
+            agg_assault: count("agg_assault")?,
+            property_crime_total: count("property_crime_total")?,
+            burglary: count("burglary")?,
+            larceny: count("larceny")?,
+            vehicle_theft: count("vehicle_theft")?,
+        });
+    }
+
+    Ok(records)
+}
+
+fn flag_text(flag: bool) -> String {
+    if flag { "True" } else { "False" }.to_string()
+}
+
+// The combined file stores UCR counts as floats ("4468912.0")
+fn count_text(count: Option<u32>) -> String {
+    count.map(|n| format!("{}.0", n)).unwrap_or_default()
+}
+
+// Left join of custody onto UCR, keyed by normalized state name and year.
+// Custody rows without UCR data are kept with empty crime columns, matching
+// how the combined file carries FEDERAL; both sides' misses are reported.
+pub fn join_sources(custody: &[CustodyRecord], ucr: &[UcrRecord]) -> CombinedJoin {
+    let ucr_index: HashMap<(String, u32), &UcrRecord> = ucr
+        .iter()
+        .map(|u| ((normalize_jurisdiction(&u.jurisdiction), u.year), u))
+        .collect();
+
+    let mut join = CombinedJoin::default();
+    let mut matched: HashSet<(String, u32)> = HashSet::new();
+
+    for c in custody {
+        let key = (normalize_jurisdiction(&c.jurisdiction), c.year);
+        let mut row = DirtyRecord {
+            jurisdiction: key.0.clone(),
+            includes_jails: flag_text(c.includes_jails),
+            year: c.year.to_string(),
+            prisoner_count: c.prisoner_count.to_string(),
+            ..Default::default()
+        };
+
+        match ucr_index.get(&key) {
+            Some(u) => {
+                row.crime_reporting_change = flag_text(u.crime_reporting_change);
+                row.crimes_estimated = flag_text(u.crimes_estimated);
+                row.state_population = count_text(u.state_population);
+                row.violent_crime_total = count_text(u.violent_crime_total);
+                row.murder_manslaughter = count_text(u.murder_manslaughter);
+                row.rape_legacy = count_text(u.rape_legacy);
+                row.rape_revised = count_text(u.rape_revised);
+                row.robbery = count_text(u.robbery);
+                row.agg_assault = count_text(u.agg_assault);
+                row.property_crime_total = count_text(u.property_crime_total);
+                row.burglary = count_text(u.burglary);
+                row.larceny = count_text(u.larceny);
+                row.vehicle_theft = count_text(u.vehicle_theft);
+                matched.insert(key);
+            }
+            None => join.unmatched_custody.push(key),
+        }
+
+        join.rows.push(row);
+    }
+
+    for u in ucr {
+        let key = (normalize_jurisdiction(&u.jurisdiction), u.year);
+        if !matched.contains(&key) {
+            join.unmatched_ucr.push(key);
+        }
+    }
+
+    // The combined file is ordered by year, then by custody file order
+    join.rows.sort_by_key(|r| r.year.parse::<u32>().unwrap_or(0));
+
+    join
+}
+
+pub fn write_combined_csv(rows: &[DirtyRecord], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_path(output_path)?;
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Rebuild crime_and_incarceration_by_state.csv from the two upstream files
+pub fn rebuild_combined_dataset(
+    custody_path: &str,
+    ucr_path: &str,
+    output_path: &str,
+) -> Result<CombinedJoin, Box<dyn Error>> {
+    let custody = load_prison_custody(custody_path)?;
+    let ucr = load_ucr(ucr_path)?;
+    let join = join_sources(&custody, &ucr);
+
+    write_combined_csv(&join.rows, output_path)?;
+
+    println!(
+        "Combined dataset written to '{}' ({} rows, {} custody rows and {} UCR rows unmatched)",
+        output_path,
+        join.rows.len(),
+        join.unmatched_custody.len(),
+        join.unmatched_ucr.len()
+    );
+
+    Ok(join)
+}
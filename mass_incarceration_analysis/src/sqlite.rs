@@ -0,0 +1,287 @@
+use crate::data_processing::{CleanRecord, DirtyRecord};
+use crate::panel::Panel;
+use crate::rates::RateSpec;
+use crate::validation::ValidationIssue;
+use rusqlite::{params, Connection};
+use std::error::Error;
+
+// Schema of the analysis database. Counts are INTEGER, flags are 0/1 and
+// `imputed` lists the imputed fields separated by ';'. Rates follow the row's
+// `rate_spec` (RateSpec::label(), e.g. "per 100k total population");
+// `rate_denominator` holds the covariate denominator when the spec uses one.
+// Rates and fit results are NULL when not finite (e.g. a zero denominator).
+//
+//   panel              one row per cleaned state-year (CleanRecord)
+//   invalid_records    rows process_dataset rejected, raw text as read (DirtyRecord)
+//   validation_issues  every issue found while cleaning, warnings included
+//   regressions        slope/intercept of crime_rate on incarceration_rate per analysis
+//   centrality         degree of each state in the similarity graph
+//   outliers           state-years flagged by identify_outliers
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS panel (
+    jurisdiction           TEXT    NOT NULL,
+    year                   INTEGER NOT NULL,
+    prisoner_count         INTEGER NOT NULL,
+    state_population       INTEGER NOT NULL,
+    violent_crime_total    INTEGER NOT NULL,
+    murder_manslaughter    INTEGER NOT NULL,
+    rape_legacy            INTEGER,
+    rape_revised           INTEGER,
+    robbery                INTEGER NOT NULL,
+    agg_assault            INTEGER NOT NULL,
+    property_crime_total   INTEGER NOT NULL,
+    burglary               INTEGER NOT NULL,
+    larceny                INTEGER NOT NULL,
+    vehicle_theft          INTEGER NOT NULL,
+    includes_jails         INTEGER NOT NULL,
+    crime_reporting_change INTEGER NOT NULL,
+    crimes_estimated       INTEGER NOT NULL,
+    incarceration_rate     REAL,
+    crime_rate             REAL,
+    imputed                TEXT    NOT NULL,
+    rate_spec              TEXT    NOT NULL,
+    rate_denominator       REAL,
+    PRIMARY KEY (jurisdiction, year)
+);
+CREATE TABLE IF NOT EXISTS invalid_records (
+    jurisdiction           TEXT,
+    includes_jails         TEXT,
+    year                   TEXT,
+    prisoner_count         TEXT,
+    crime_reporting_change TEXT,
+    crimes_estimated       TEXT,
+    state_population       TEXT,
+    violent_crime_total    TEXT,
+    murder_manslaughter    TEXT,
+    rape_legacy            TEXT,
+    rape_revised           TEXT,
+    robbery                TEXT,
+    agg_assault            TEXT,
+    property_crime_total   TEXT,
+    burglary               TEXT,
+    larceny                TEXT,
+    vehicle_theft          TEXT
+);
+CREATE TABLE IF NOT EXISTS validation_issues (
+    jurisdiction TEXT,
+    year         TEXT,
+    field        TEXT,
+    raw_value    TEXT,
+    reason       TEXT,
+    severity     TEXT
+);
+CREATE TABLE IF NOT EXISTS regressions (
+    label     TEXT PRIMARY KEY,
+    slope     REAL,
+    intercept REAL,
+    records   INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS centrality (
+    jurisdiction TEXT PRIMARY KEY,
+    degree       INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS outliers (
+    jurisdiction       TEXT    NOT NULL,
+    year               INTEGER NOT NULL,
+    incarceration_rate REAL,
+    crime_rate         REAL
+);
+";
+
+// NaN and infinities are stored as NULL rather than left to the driver
+fn finite(value: f32) -> Option<f32> {
+    value.is_finite().then_some(value)
+}
+
+// Open (or create) the database file and make sure every table exists
+pub fn open_database(db_path: &str) -> Result<Connection, Box<dyn Error>> {
+    let conn = Connection::open(db_path)?;
+    conn.execute_batch(SCHEMA)?;
+    Ok(conn)
+}
+
+// Replaces the contents of `panel`
+pub fn write_panel(conn: &mut Connection, records: &[CleanRecord]) -> Result<(), Box<dyn Error>> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM panel", [])?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO panel VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
+        )?;
+        for r in records {
+            stmt.execute(params![
+                r.jurisdiction,
+                r.year,
+                r.prisoner_count,
+                r.state_population,
+                r.violent_crime_total,
+                r.murder_manslaughter,
+                r.rape_legacy,
+                r.rape_revised,
+                r.robbery,
+                r.agg_assault,
+                r.property_crime_total,
+                r.burglary,
+                r.larceny,
+                r.vehicle_theft,
+                r.includes_jails,
+                r.crime_reporting_change,
+                r.crimes_estimated,
+                finite(r.incarceration_rate),
+                finite(r.crime_rate),
+                r.imputed.join(";"),
+                r.rate_spec.label(),
+                r.denominator_covariate(),
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn write_invalid_records(conn: &mut Connection, invalid: &[DirtyRecord]) -> Result<(), Box<dyn Error>> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM invalid_records", [])?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO invalid_records VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+        )?;
+        for d in invalid {
+            stmt.execute(params![
+                d.jurisdiction,
+                d.includes_jails,
+                d.year,
+                d.prisoner_count,
+                d.crime_reporting_change,
+                d.crimes_estimated,
+                d.state_population,
+                d.violent_crime_total,
+                d.murder_manslaughter,
+                d.rape_legacy,
+                d.rape_revised,
+                d.robbery,
+                d.agg_assault,
+                d.property_crime_total,
+                d.burglary,
+                d.larceny,
+                d.vehicle_theft,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn write_validation_issues(conn: &mut Connection, issues: &[ValidationIssue]) -> Result<(), Box<dyn Error>> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM validation_issues", [])?;
+    {
+        let mut stmt = tx.prepare("INSERT INTO validation_issues VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+        for i in issues {
+            stmt.execute(params![
+                i.jurisdiction,
+                i.year,
+                i.field,
+                i.raw_value,
+                format!("{:?}", i.reason),
+                format!("{:?}", i.severity),
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+// Insert or replace one regression result, e.g. ("all states", slope, intercept, n)
+pub fn write_regression(
+    conn: &mut Connection,
+    label: &str,
+    slope: f32,
+    intercept: f32,
+    records: usize,
+) -> Result<(), Box<dyn Error>> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT OR REPLACE INTO regressions VALUES (?1, ?2, ?3, ?4)",
+        params![label, finite(slope), finite(intercept), records as i64],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn write_centrality(conn: &mut Connection, centrality: &[(String, usize)]) -> Result<(), Box<dyn Error>> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM centrality", [])?;
+    {
+        let mut stmt = tx.prepare("INSERT INTO centrality VALUES (?1, ?2)")?;
+        for (state, degree) in centrality {
+            stmt.execute(params![state, *degree as i64])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn write_outliers(conn: &mut Connection, outliers: &[CleanRecord]) -> Result<(), Box<dyn Error>> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM outliers", [])?;
+    {
+        let mut stmt = tx.prepare("INSERT INTO outliers VALUES (?1, ?2, ?3, ?4)")?;
+        for r in outliers {
+            stmt.execute(params![r.jurisdiction, r.year, finite(r.incarceration_rate), finite(r.crime_rate)])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+// Rebuild the panel from the `panel` table; rates are recomputed from the
+// counts under each row's stored rate spec
+pub fn load_panel(db_path: &str) -> Result<Panel, Box<dyn Error>> {
+    let conn = Connection::open(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT jurisdiction, year, prisoner_count, state_population, violent_crime_total,
+                murder_manslaughter, rape_legacy, rape_revised, robbery, agg_assault,
+                property_crime_total, burglary, larceny, vehicle_theft,
+                includes_jails, crime_reporting_change, crimes_estimated, imputed,
+                rate_spec, rate_denominator
+         FROM panel",
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        let imputed: String = row.get(17)?;
+        let rate_spec: String = row.get(18)?;
+        let rate_denominator: Option<f64> = row.get(19)?;
+        let record = CleanRecord {
+            jurisdiction: row.get(0)?,
+            year: row.get(1)?,
+            prisoner_count: row.get(2)?,
+            state_population: row.get(3)?,
+            violent_crime_total: row.get(4)?,
+            murder_manslaughter: row.get(5)?,
+            rape_legacy: row.get(6)?,
+            rape_revised: row.get(7)?,
+            robbery: row.get(8)?,
+            agg_assault: row.get(9)?,
+            property_crime_total: row.get(10)?,
+            burglary: row.get(11)?,
+            larceny: row.get(12)?,
+            vehicle_theft: row.get(13)?,
+            includes_jails: row.get(14)?,
+            crime_reporting_change: row.get(15)?,
+            crimes_estimated: row.get(16)?,
+            imputed: imputed.split(';').filter(|f| !f.is_empty()).map(String::from).collect(),
+            ..Default::default()
+        };
+        Ok((record, rate_spec, rate_denominator))
+    })?;
+
+    let mut records = Vec::new();
+    for row in rows {
+        let (mut record, label, denominator) = row?;
+        let spec = RateSpec::from_label(&label).ok_or_else(|| format!("Unknown rate spec '{}' in panel", label))?;
+        record.restore_rate_spec(spec, denominator);
+        records.push(record);
+    }
+    Ok(Panel::new(records))
+}
@@ -0,0 +1,150 @@
+use crate::data_processing::normalize_jurisdiction;
+
+// Census Bureau regions and divisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Region {
+    Northeast,
+    Midwest,
+    South,
+    West,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Division {
+    NewEngland,
+    MiddleAtlantic,
+    EastNorthCentral,
+    WestNorthCentral,
+    SouthAtlantic,
+    EastSouthCentral,
+    WestSouthCentral,
+    Mountain,
+    Pacific,
+}
+
+impl Division {
+    pub fn region(&self) -> Region {
+        match self {
+            Division::NewEngland | Division::MiddleAtlantic => Region::Northeast,
+            Division::EastNorthCentral | Division::WestNorthCentral => Region::Midwest,
+            Division::SouthAtlantic | Division::EastSouthCentral | Division::WestSouthCentral => Region::South,
+            Division::Mountain | Division::Pacific => Region::West,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateInfo {
+    pub name: &'static str,    // Canonical upper-case name, as in the combined CSV
+    pub display: &'static str, // Title-case name, as in the raw sources
+    pub postal: &'static str,
+    pub fips: u8,
+    pub division: Option<Division>, // None for territories outside the census regions
+}
+
+impl StateInfo {
+    pub fn region(&self) -> Option<Region> {
+        self.division.map(|d| d.region())
+    }
+}
+
+// Federal prisoners are reported as their own jurisdiction next to the states
+pub const FEDERAL: &str = "FEDERAL";
+
+use Division::*;
+
+const fn state(name: &'static str, display: &'static str, postal: &'static str, fips: u8, division: Division) -> StateInfo {
+    StateInfo { name, display, postal, fips, division: Some(division) }
+}
+
+// The 50 states, DC, and Puerto Rico (which appears in the UCR file)
+pub static STATES: [StateInfo; 52] = [
+    state("ALABAMA", "Alabama", "AL", 1, EastSouthCentral),
+    state("ALASKA", "Alaska", "AK", 2, Pacific),
+    state("ARIZONA", "Arizona", "AZ", 4, Mountain),
+    state("ARKANSAS", "Arkansas", "AR", 5, WestSouthCentral),
+    state("CALIFORNIA", "California", "CA", 6, Pacific),
+    state("COLORADO", "Colorado", "CO", 8, Mountain),
+    state("CONNECTICUT", "Connecticut", "CT", 9, NewEngland),
+    state("DELAWARE", "Delaware", "DE", 10, SouthAtlantic),
+    state("DISTRICT OF COLUMBIA", "District of Columbia", "DC", 11, SouthAtlantic),
+    state("FLORIDA", "Florida", "FL", 12, SouthAtlantic),
+    state("GEORGIA", "Georgia", "GA", 13, SouthAtlantic),
+    state("HAWAII", "Hawaii", "HI", 15, Pacific),
+    state("IDAHO", "Idaho", "ID", 16, Mountain),
+    state("ILLINOIS", "Illinois", "IL", 17, EastNorthCentral),
+    state("INDIANA", "Indiana", "IN", 18, EastNorthCentral),
+    state("IOWA", "Iowa", "IA", 19, WestNorthCentral),
+    state("KANSAS", "Kansas", "KS", 20, WestNorthCentral),
+    state("KENTUCKY", "Kentucky", "KY", 21, EastSouthCentral),
+    state("LOUISIANA", "Louisiana", "LA", 22, WestSouthCentral),
+    state("MAINE", "Maine", "ME", 23, NewEngland),
+    state("MARYLAND", "Maryland", "MD", 24, SouthAtlantic),
+    state("MASSACHUSETTS", "Massachusetts", "MA", 25, NewEngland),
+    state("MICHIGAN", "Michigan", "MI", 26, EastNorthCentral),
+    state("MINNESOTA", "Minnesota", "MN", 27, WestNorthCentral),
+    state("MISSISSIPPI", "Mississippi", "MS", 28, EastSouthCentral),
+    state("MISSOURI", "Missouri", "MO", 29, WestNorthCentral),
+    state("MONTANA", "Montana", "MT", 30, Mountain),
+    state("NEBRASKA", "Nebraska", "NE", 31, WestNorthCentral),
+    state("NEVADA", "Nevada", "NV", 32, Mountain),
+    state("NEW HAMPSHIRE", "New Hampshire", "NH", 33, NewEngland),
+    state("NEW JERSEY", "New Jersey", "NJ", 34, MiddleAtlantic),
+    state("NEW MEXICO", "New Mexico", "NM", 35, Mountain),
+    state("NEW YORK", "New York", "NY", 36, MiddleAtlantic),
+    state("NORTH CAROLINA", "North Carolina", "NC", 37, SouthAtlantic),
+    state("NORTH DAKOTA", "North Dakota", "ND", 38, WestNorthCentral),
+    state("OHIO", "Ohio", "OH", 39, EastNorthCentral),
+    state("OKLAHOMA", "Oklahoma", "OK", 40, WestSouthCentral),
+    state("OREGON", "Oregon", "OR", 41, Pacific),
+    state("PENNSYLVANIA", "Pennsylvania", "PA", 42, MiddleAtlantic),
+    state("RHODE ISLAND", "Rhode Island", "RI", 44, NewEngland),
+    state("SOUTH CAROLINA", "South Carolina", "SC", 45, SouthAtlantic),
+    state("SOUTH DAKOTA", "South Dakota", "SD", 46, WestNorthCentral),
+    state("TENNESSEE", "Tennessee", "TN", 47, EastSouthCentral),
+    state("TEXAS", "Texas", "TX", 48, WestSouthCentral),
+    state("UTAH", "Utah", "UT", 49, Mountain),
+    state("VERMONT", "Vermont", "VT", 50, NewEngland),
+    state("VIRGINIA", "Virginia", "VA", 51, SouthAtlantic),
+    state("WASHINGTON", "Washington", "WA", 53, Pacific),
+    state("WEST VIRGINIA", "West Virginia", "WV", 54, SouthAtlantic),
+    state("WISCONSIN", "Wisconsin", "WI", 55, EastNorthCentral),
+    state("WYOMING", "Wyoming", "WY", 56, Mountain),
+    StateInfo { name: "PUERTO RICO", display: "Puerto Rico", postal: "PR", fips: 72, division: None },
+];
+
+// Match a full name in any case/spacing, a postal code, or a FIPS code ("4" or "04")
+pub fn lookup_state(name: &str) -> Option<&'static StateInfo> {
+    let key = name
+        .trim_start_matches('\u{feff}')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase();
+
+    if let Ok(fips) = key.parse::<u8>() {
+        return STATES.iter().find(|s| s.fips == fips);
+    }
+
+    STATES.iter().find(|s| s.name == key || s.postal == key)
+}
+
+pub fn lookup_fips(fips: u8) -> Option<&'static StateInfo> {
+    STATES.iter().find(|s| s.fips == fips)
+}
+
+// Canonical jurisdiction name (a state or FEDERAL), or None if unrecognized
+pub fn resolve_jurisdiction(name: &str) -> Option<String> {
+    if normalize_jurisdiction(name) == FEDERAL {
+        return Some(FEDERAL.to_string());
+    }
+    lookup_state(name).map(|s| s.name.to_string())
+}
+
+pub fn region_of(name: &str) -> Option<Region> {
+    lookup_state(name).and_then(|s| s.region())
+}
+
+pub fn division_of(name: &str) -> Option<Division> {
+    lookup_state(name).and_then(|s| s.division)
+}
@@ -0,0 +1,161 @@
+use crate::data_processing::DirtyRecord;
+use crate::states::resolve_jurisdiction;
+use csv::Writer;
+use serde::Serialize;
+use std::error::Error;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IssueReason {
+    Missing,
+    Unparseable,
+    Zero,
+    Negative,
+    Overflow,
+    Unrecognized, // Jurisdiction not in the state registry
+}
+
+// Error drops the row; Warning keeps it with the value coerced (usually to 0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub jurisdiction: String,
+    pub year: String,
+    pub field: String,
+    pub raw_value: String,
+    pub reason: IssueReason,
+    pub severity: Severity,
+}
+
+// Collects the issues found while cleaning one DirtyRecord
+pub(crate) struct RecordChecker {
+    jurisdiction: String,
+    year: String,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl RecordChecker {
+    pub fn new(r: &DirtyRecord) -> Self {
+        RecordChecker {
+            jurisdiction: r.jurisdiction.clone(),
+            year: r.year.clone(),
+            issues: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, field: &str, raw: &str, reason: IssueReason, severity: Severity) {
+        self.issues.push(ValidationIssue {
+            jurisdiction: self.jurisdiction.clone(),
+            year: self.year.clone(),
+            field: field.to_string(),
+            raw_value: raw.to_string(),
+            reason,
+            severity,
+        });
+    }
+
+    // Canonical name from the state registry; unknown names are kept as written
+    pub fn jurisdiction(&mut self, raw: &str) -> String {
+        match resolve_jurisdiction(raw) {
+            Some(name) => name,
+            None => {
+                let reason = if raw.trim().is_empty() { IssueReason::Missing } else { IssueReason::Unrecognized };
+                self.push("jurisdiction", raw, reason, Severity::Error);
+                raw.to_string()
+            }
+        }
+    }
+
+    pub fn year(&mut self, raw: &str) -> Option<u32> {
+        let trimmed = raw.trim().trim_matches('"');
+        if trimmed.is_empty() {
+            self.push("year", raw, IssueReason::Missing, Severity::Error);
+            return None;
+        }
+        match trimmed.parse::<u32>() {
+            Ok(year) => Some(year),
+            Err(_) => {
+                self.push("year", raw, IssueReason::Unparseable, Severity::Error);
+                None
+            }
+        }
+    }
+
+    // Parse a count such as "4468912.0" or "149,852". `missing` is the severity of an
+    // empty cell (None when the column may legitimately be blank) and `zero` the
+    // severity of a zero count. Unreadable values are as severe as missing ones.
+    pub fn count(
+        &mut self,
+        field: &str,
+        raw: &str,
+        missing: Option<Severity>,
+        zero: Option<Severity>,
+    ) -> Option<u32> {
+        let bad_value = missing.unwrap_or(Severity::Warning);
+        let trimmed = raw.trim().trim_matches('"').replace(",", "");
+        if trimmed.is_empty() {
+            if let Some(severity) = missing {
+                self.push(field, raw, IssueReason::Missing, severity);
+            }
+            return None;
+        }
+
+        let value = match trimmed.parse::<f64>() {
+            Ok(v) if v.is_finite() => v.round(),
+            _ => {
+                self.push(field, raw, IssueReason::Unparseable, bad_value);
+                return None;
+            }
+        };
+
+        if value < 0.0 {
+            self.push(field, raw, IssueReason::Negative, bad_value);
+            return None;
+        }
+        if value > u32::MAX as f64 {
+            self.push(field, raw, IssueReason::Overflow, bad_value);
+            return None;
+        }
+        if value == 0.0 {
+            if let Some(severity) = zero {
+                self.push(field, raw, IssueReason::Zero, severity);
+            }
+        }
+
+        Some(value as u32)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+}
+
+pub fn write_issues_csv(issues: &[ValidationIssue], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_path(output_path)?;
+    for issue in issues {
+        wtr.serialize(issue)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn write_issues_json(issues: &[ValidationIssue], output_path: &str) -> Result<(), Box<dyn Error>> {
+    fs::write(output_path, serde_json::to_string_pretty(issues)?)?;
+    Ok(())
+}
+
+// Print a one-line count per severity, e.g. for the end of a cleaning run
+pub fn summarize_issues(issues: &[ValidationIssue]) {
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    println!(
+        "Validation: {} issues ({} errors, {} warnings)",
+        issues.len(),
+        errors,
+        issues.len() - errors
+    );
+}
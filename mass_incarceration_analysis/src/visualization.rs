@@ -1,4 +1,6 @@
 use crate::data_processing::CleanRecord;
+use crate::federal::NationalTotal;
+use crate::breaks::{breaks_for_state, BreakKind};
 use plotters::prelude::*;
 use std::error::Error;
 use std::collections::HashMap;
@@ -122,25 +124,76 @@ pub fn plot_national_averages(records: &[CleanRecord]) -> Result<(), Box<dyn std
         &BLUE,
     ))?
     .label("Incarceration Rate")
-    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], &BLUE));
+    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], BLUE));
 
     chart.draw_series(LineSeries::new(
         years.iter().zip(averages.iter()).map(|(year, (_, crime_rate))| (*year, *crime_rate)),
         &RED,
     ))?
     .label("Crime Rate")
-    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], &RED));
+    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], RED));
 
     // Step 4: Add legend
     chart
         .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()?;
 
     println!("Plot saved as 'output/national_averages.png'.");
     Ok(())
 }
+pub fn plot_federal_trends(totals: &[NationalTotal]) -> Result<(), Box<dyn Error>> {
+    if totals.is_empty() {
+        println!("No national totals to plot.");
+        return Ok(());
+    }
+
+    let root = BitMapBackend::new("output/federal_trends_over_time.png", (1200, 800)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let min_year = totals.first().unwrap().year;
+    let max_year = totals.last().unwrap().year;
+    let max_prisoners = totals.iter().map(|t| t.total_prisoners).max().unwrap_or(1) as f64;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("State and Federal Prisoners Over Time", ("Arial", 30))
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .margin(10)
+        .build_cartesian_2d(min_year..max_year, 0.0..(max_prisoners * 1.1))?;
+
+    chart.configure_mesh().x_desc("Year").y_desc("Prisoners").draw()?;
+
+    chart
+        .draw_series(LineSeries::new(
+            totals.iter().map(|t| (t.year, t.federal_prisoners as f64)),
+            &RED,
+        ))?
+        .label("Federal")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RED));
+
+    chart
+        .draw_series(LineSeries::new(
+            totals.iter().map(|t| (t.year, t.state_prisoners as f64)),
+            &BLUE,
+        ))?
+        .label("States")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLUE));
+
+    chart
+        .draw_series(LineSeries::new(
+            totals.iter().map(|t| (t.year, t.total_prisoners as f64)),
+            &BLACK,
+        ))?
+        .label("State + Federal")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLACK));
+
+    chart.configure_series_labels().background_style(WHITE).draw()?;
+
+    println!("Federal trend chart saved to 'output/federal_trends_over_time.png'");
+    Ok(())
+}
 pub fn plot_trends_over_time(records: &[CleanRecord], state: Option<&str>) -> Result<(), Box<dyn Error>> {
     // Filter records for the specified state, if provided
     let filtered_records: Vec<&CleanRecord> = match state {
@@ -175,7 +228,7 @@ pub fn plot_trends_over_time(records: &[CleanRecord], state: Option<&str>) -> Re
         .iter()
         .chain(crime_rates.iter())
         .copied()
-        .fold(0.0 / 0.0, f32::max); // Find max for y-axis
+        .fold(f32::NAN, f32::max); // Find max for y-axis
 
     let mut chart = ChartBuilder::on(&root)
         .caption(
@@ -202,7 +255,7 @@ pub fn plot_trends_over_time(records: &[CleanRecord], state: Option<&str>) -> Re
             &BLUE,
         ))?
         .label("Incarceration Rate")
-        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], &BLUE));
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLUE));
 
     // Draw crime rate line
     chart
@@ -211,10 +264,52 @@ pub fn plot_trends_over_time(records: &[CleanRecord], state: Option<&str>) -> Re
             &RED,
         ))?
         .label("Crime Rate")
-        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], &RED));
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RED));
+
+    // Hollow markers on both lines for imputed years
+    let imputed: Vec<(u32, f32, f32)> = filtered_records
+        .iter()
+        .filter(|r| r.is_imputed())
+        .map(|r| (r.year, r.incarceration_rate, r.crime_rate))
+        .collect();
+    if !imputed.is_empty() {
+        chart
+            .draw_series(imputed.iter().flat_map(|&(year, inc_rate, crime_rate)| {
+                [Circle::new((year, inc_rate), 5, BLACK), Circle::new((year, crime_rate), 5, BLACK)]
+            }))?
+            .label("Imputed")
+            .legend(|(x, y)| Circle::new((x + 10, y), 5, BLACK));
+    }
+
+    // Mark series breaks: a vertical line at each reporting change, a cross on
+    // the crime line for each estimated year
+    if let Some(state_name) = state {
+        let breaks = breaks_for_state(records, state_name);
+        let changes: Vec<u32> = breaks.iter().filter(|b| b.kind == BreakKind::ReportingChange).map(|b| b.year).collect();
+        let estimated: Vec<(u32, f32)> = breaks
+            .iter()
+            .filter(|b| b.kind == BreakKind::Estimated)
+            .filter_map(|b| years.iter().position(|&y| y == b.year).map(|i| (b.year, crime_rates[i])))
+            .collect();
+
+        if !changes.is_empty() {
+            chart
+                .draw_series(changes.iter().map(|&year| {
+                    PathElement::new(vec![(year, 0.0), (year, max_y * 1.2)], BLACK.mix(0.6))
+                }))?
+                .label("Reporting Change")
+                .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLACK.mix(0.6)));
+        }
+        if !estimated.is_empty() {
+            chart
+                .draw_series(estimated.iter().map(|&(year, rate)| Cross::new((year, rate), 6, MAGENTA)))?
+                .label("Estimated")
+                .legend(|(x, y)| Cross::new((x + 10, y), 6, MAGENTA));
+        }
+    }
 
     // Add a legend
-    chart.configure_series_labels().background_style(&WHITE).draw()?;
+    chart.configure_series_labels().background_style(WHITE).draw()?;
 
     println!("Trend chart saved to '{}'", file_name);
     Ok(())
@@ -269,7 +364,7 @@ pub fn plot_trends_over_time(records: &[CleanRecord], state: Option<&str>) -> Re
     
             chart
                 .draw_series(LineSeries::new(
-                    years.into_iter().zip(crime_rates.into_iter()),
+                    years.into_iter().zip(crime_rates),
                     *colors.get(i % colors.len()).unwrap(),
                 ))?
                 .label(format!("{} Crime Rate", state))
@@ -277,7 +372,7 @@ pub fn plot_trends_over_time(records: &[CleanRecord], state: Option<&str>) -> Re
         }
     
         // Add a legend
-        chart.configure_series_labels().background_style(&WHITE).draw()?;
+        chart.configure_series_labels().background_style(WHITE).draw()?;
     
         println!("Crime rate comparison chart saved to '{}'", file_name);
     
@@ -0,0 +1,30 @@
+use mass_incarceration_analysis::anomalies::{detect_yoy_anomalies, quarantine, AnomalyConfig};
+use mass_incarceration_analysis::data_processing::process_dataset;
+
+#[test]
+fn test_injected_glitches_are_flagged() {
+    let (mut records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let baseline = detect_yoy_anomalies(&records, AnomalyConfig::default());
+
+    for r in records.iter_mut() {
+        if r.jurisdiction == "TEXAS" && r.year == 2008 {
+            r.prisoner_count *= 2;
+        }
+        if r.jurisdiction == "OHIO" && r.year == 2010 {
+            r.state_population = (r.state_population as f64 * 0.6) as u32;
+        }
+    }
+    let anomalies = detect_yoy_anomalies(&records, AnomalyConfig::default());
+    assert_eq!(anomalies.len(), baseline.len() + 2);
+
+    let texas = anomalies.iter().find(|a| a.jurisdiction == "TEXAS" && a.field == "prisoner_count").unwrap();
+    assert_eq!(texas.year, 2008); // The drop back in 2009 is not reported separately
+    assert!(texas.robust_z > 5.0);
+    let ohio = anomalies.iter().find(|a| a.jurisdiction == "OHIO" && a.field == "state_population").unwrap();
+    assert_eq!(ohio.year, 2010);
+
+    let (kept, quarantined) = quarantine(&records, &anomalies);
+    assert_eq!(kept.len() + quarantined.len(), records.len());
+    assert!(quarantined.iter().any(|r| r.jurisdiction == "TEXAS" && r.year == 2008));
+    assert!(!kept.iter().any(|r| r.jurisdiction == "OHIO" && r.year == 2010));
+}
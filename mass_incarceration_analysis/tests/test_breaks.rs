@@ -0,0 +1,50 @@
+use mass_incarceration_analysis::breaks::{analysis_records, apply_break_handling, BreakHandling, BreakKind};
+use mass_incarceration_analysis::data_processing::{process_dataset, CleanRecord};
+use mass_incarceration_analysis::panel::Panel;
+
+#[test]
+fn test_flags_become_series_breaks() {
+    let (records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let panel = Panel::new(records);
+
+    let alabama = panel.breaks("Alabama");
+    assert_eq!(alabama.len(), 1);
+    assert_eq!(alabama[0].year, 2011);
+    assert_eq!(alabama[0].kind, BreakKind::ReportingChange);
+
+    assert!(panel.breaks("Illinois").iter().all(|b| b.kind == BreakKind::Estimated));
+}
+
+#[test]
+fn test_break_handling_modes() {
+    let (records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let alabama: Vec<_> = records.iter().filter(|r| r.jurisdiction == "ALABAMA").cloned().collect();
+
+    let excluded = apply_break_handling(&alabama, BreakHandling::Exclude);
+    assert_eq!(excluded[0].len(), alabama.len() - 1);
+
+    // The reporting change year starts the second segment
+    let segments = apply_break_handling(&alabama, BreakHandling::Split);
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[1][0].year, 2011);
+}
+
+#[test]
+fn test_analysis_records_follow_break_handling() {
+    let record = |year: u32, crime_reporting_change: bool, crimes_estimated: bool| CleanRecord {
+        jurisdiction: "ALABAMA".to_string(),
+        year,
+        crime_reporting_change,
+        crimes_estimated,
+        ..Default::default()
+    };
+    let series = vec![record(2009, false, true), record(2010, false, false), record(2011, true, false), record(2012, false, false)];
+
+    assert_eq!(analysis_records(&series, BreakHandling::Include).len(), 4);
+    let excluded = analysis_records(&series, BreakHandling::Exclude);
+    assert_eq!(excluded.iter().map(|r| r.year).collect::<Vec<_>>(), vec![2010, 2012]);
+
+    // Split keeps the years since the reporting change
+    let latest = analysis_records(&series, BreakHandling::Split);
+    assert_eq!(latest.iter().map(|r| r.year).collect::<Vec<_>>(), vec![2011, 2012]);
+}
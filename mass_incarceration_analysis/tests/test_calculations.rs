@@ -11,6 +11,7 @@ fn test_linear_regression() {
             violent_crime_total: 28675,
             incarceration_rate: 522.1439,
             crime_rate: 540.3276,
+            ..Default::default()
         },
         CleanRecord {
             jurisdiction: "ARIZONA".to_string(),
@@ -20,8 +21,38 @@ fn test_linear_regression() {
             violent_crime_total: 30171,
             incarceration_rate: 539.5759,
             crime_rate: 554.4993,
+            ..Default::default()
         },
     ];
     let result = linear_regression(&records);
     assert!(result.is_ok());
-}
\ No newline at end of file
+}
+#[test]
+fn test_regression_fit_statistics() {
+    // Textbook data: y = 2.2 + 0.6x, R² = 0.6
+    let records: Vec<CleanRecord> = [(1.0, 2.0), (2.0, 4.0), (3.0, 5.0), (4.0, 4.0), (5.0, 5.0)]
+        .iter()
+        .map(|&(x, y)| CleanRecord {
+            incarceration_rate: x,
+            crime_rate: y,
+            ..Default::default()
+        })
+        .collect();
+    let fit = linear_regression(&records).unwrap();
+
+    assert_eq!(fit.n, 5);
+    assert!((fit.slope - 0.6).abs() < 1e-9);
+    assert!((fit.intercept - 2.2).abs() < 1e-9);
+    assert!((fit.r_squared - 0.6).abs() < 1e-9);
+    assert!((fit.adj_r_squared - 0.4667).abs() < 1e-4);
+    assert!((fit.residual_std_error - 0.8944).abs() < 1e-4);
+    assert!((fit.slope_se - 0.2828).abs() < 1e-4);
+    assert!((fit.slope_t - 2.1213).abs() < 1e-4);
+    assert!((fit.slope_p - 0.1240).abs() < 1e-3);
+    assert!(fit.slope_ci.0 < 0.0 && fit.slope_ci.1 > 1.2);
+    assert!(fit.residuals.iter().sum::<f64>().abs() < 1e-9);
+    assert_eq!(fit.fitted.len(), 5);
+
+    let json: serde_json::Value = serde_json::from_str(&fit.to_json().unwrap()).unwrap();
+    assert_eq!(json["n"], 5);
+}
@@ -0,0 +1,88 @@
+use mass_incarceration_analysis::columnar::{panel_frame, read_ipc, read_parquet, write_ipc, write_parquet};
+use mass_incarceration_analysis::data_processing::{process_dataset, CleanRecord};
+use mass_incarceration_analysis::rates::{apply_rate_spec, Denominator, RateScale, RateSpec};
+
+#[test]
+fn test_parquet_and_ipc_round_trip() {
+    let (records, _, issues) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let dir = std::env::temp_dir();
+    let parquet = dir.join("mia_test_panel.parquet");
+    let ipc = dir.join("mia_test_panel.arrow");
+
+    write_parquet(&records, &issues, parquet.to_str().unwrap()).unwrap();
+    write_ipc(&records, &issues, ipc.to_str().unwrap()).unwrap();
+
+    for back in [read_parquet(parquet.to_str().unwrap()).unwrap(), read_ipc(ipc.to_str().unwrap()).unwrap()] {
+        assert_eq!(back.len(), records.len());
+        for (a, b) in records.iter().zip(&back) {
+            assert_eq!((&a.jurisdiction, a.year, a.prisoner_count), (&b.jurisdiction, b.year, b.prisoner_count));
+            assert_eq!((a.rape_legacy, a.rape_revised), (b.rape_legacy, b.rape_revised));
+            assert_eq!(a.crimes_estimated, b.crimes_estimated);
+            assert!((a.incarceration_rate - b.incarceration_rate).abs() < 1e-3);
+        }
+    }
+}
+
+#[test]
+fn test_panel_frame_carries_rates_and_warnings() {
+    let (records, _, issues) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let df = panel_frame(&records, &issues).unwrap();
+    assert_eq!(df.height(), records.len());
+    for name in ["incarceration_rate", "crime_rate", "crime_reporting_change", "imputed", "validation_warnings"] {
+        assert!(df.column(name).is_ok(), "{}", name);
+    }
+}
+
+#[test]
+fn test_round_trip_keeps_rate_spec() {
+    let mut record = CleanRecord {
+        jurisdiction: "OHIO".to_string(),
+        year: 2010,
+        prisoner_count: 50_000,
+        state_population: 11_500_000,
+        violent_crime_total: 35_000,
+        ..Default::default()
+    };
+    record.covariates.insert("adult_population".to_string(), 8_800_000.0);
+    let spec = RateSpec::new(RateScale::PerThousand, Denominator::AdultPopulation);
+    let records = apply_rate_spec(&[record], &spec);
+
+    let dir = std::env::temp_dir();
+    let parquet = dir.join("mia_test_spec.parquet");
+    let ipc = dir.join("mia_test_spec.arrow");
+    write_parquet(&records, &[], parquet.to_str().unwrap()).unwrap();
+    write_ipc(&records, &[], ipc.to_str().unwrap()).unwrap();
+
+    for back in [read_parquet(parquet.to_str().unwrap()).unwrap(), read_ipc(ipc.to_str().unwrap()).unwrap()] {
+        assert_eq!(back[0].rate_spec, spec);
+        assert!((back[0].incarceration_rate - 50_000.0 / 8_800.0).abs() < 1e-3);
+        assert!((back[0].crime_rate - records[0].crime_rate).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_round_trip_keeps_warnings_and_covariates() {
+    let mut record = CleanRecord {
+        jurisdiction: "OHIO".to_string(),
+        year: 2010,
+        prisoner_count: 50_000,
+        state_population: 11_500_000,
+        violent_crime_total: 35_000,
+        rape_legacy: Some(4_000),
+        validation_warnings: vec!["robbery".to_string(), "burglary".to_string()],
+        ..Default::default()
+    };
+    record.covariates.insert("unemployment".to_string(), 10.1);
+    record.covariates.insert("median_age".to_string(), 39.0);
+    record.compute_rates();
+    let records = vec![record];
+
+    let dir = std::env::temp_dir();
+    let parquet = dir.join("mia_test_warnings.parquet");
+    let ipc = dir.join("mia_test_warnings.arrow");
+    write_parquet(&records, &[], parquet.to_str().unwrap()).unwrap();
+    write_ipc(&records, &[], ipc.to_str().unwrap()).unwrap();
+
+    assert_eq!(read_parquet(parquet.to_str().unwrap()).unwrap(), records);
+    assert_eq!(read_ipc(ipc.to_str().unwrap()).unwrap(), records);
+}
@@ -0,0 +1,47 @@
+use mass_incarceration_analysis::covariates::{
+    attach_covariates, construct_feature_similarity_graph, load_covariates, with_covariate,
+};
+use mass_incarceration_analysis::data_processing::process_dataset;
+use mass_incarceration_analysis::ols::{build_design_matrix, OlsSpec, Regressor};
+
+fn covariate_file() -> String {
+    let path = std::env::temp_dir().join("mia_test_covariates.csv");
+    std::fs::write(
+        &path,
+        "State,Year,unemployment,median_age\n\
+         Texas,2010,8.1,33.6\n\
+         tx,2011,7.8,33.8\n\
+         new york,2010,8.6,NA\n\
+         Ohio,2010,\"10,1\",39.0\n",
+    )
+    .unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_covariates_join_through_state_registry() {
+    let table = load_covariates(&covariate_file()).unwrap();
+    assert_eq!(table.names, vec!["unemployment", "median_age"]);
+    assert_eq!(table.get("TX", 2011, "unemployment"), Some(7.8));
+    assert_eq!(table.get("New York", 2010, "median_age"), None);
+
+    let (records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let joined = attach_covariates(&records, &table);
+    let texas = joined.iter().find(|r| r.jurisdiction == "TEXAS" && r.year == 2011).unwrap();
+    assert_eq!(texas.covariate("unemployment"), Some(7.8));
+
+    // Only state-years with the covariate take part in the regressions
+    let swapped = with_covariate(&joined, "unemployment");
+    assert_eq!(swapped.len(), 4);
+    let texas = swapped.iter().find(|r| r.jurisdiction == "TEXAS" && r.year == 2011).unwrap();
+    assert!((texas.crime_rate - 7.8).abs() < 1e-6);
+
+    let mut spec = OlsSpec::new("incarceration_rate", ["unemployment", "median_age"].map(Regressor::feature).to_vec());
+    spec.intercept = false;
+    let design = build_design_matrix(&joined, &spec).unwrap();
+    assert_eq!(design.x.dim(), (3, 2));
+
+    let graph = construct_feature_similarity_graph(&joined, &["unemployment", "median_age"], 0.0);
+    assert_eq!(graph.node_count(), 3);
+    assert_eq!(graph.edge_count(), 3);
+}
@@ -15,6 +15,7 @@ mod tests {
                 violent_crime_total: 2000,
                 incarceration_rate: 50.0,
                 crime_rate: 200.0,
+                ..Default::default()
             },
             CleanRecord {
                 jurisdiction: "State2".to_string(),
@@ -24,6 +25,7 @@ mod tests {
                 violent_crime_total: 3000,
                 incarceration_rate: 100.0,
                 crime_rate: 300.0,
+                ..Default::default()
             },
             CleanRecord {
                 jurisdiction: "State3".to_string(),
@@ -33,6 +35,7 @@ mod tests {
                 violent_crime_total: 4000,
                 incarceration_rate: 150.0,
                 crime_rate: 400.0,
+                ..Default::default()
             },
         ];
         // Call the function
@@ -41,4 +44,47 @@ mod tests {
         assert!(result.is_ok());
         // (Optional) Refactor linear_regression to return values for direct validation
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_offense_columns_are_carried() {
+        use mass_incarceration_analysis::{process_dataset, with_crime_measure, Offense};
+
+        let (records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+        let alabama = records
+            .iter()
+            .find(|r| r.jurisdiction == "ALABAMA" && r.year == 2001)
+            .unwrap();
+
+        assert_eq!(alabama.property_crime_total, 173253);
+        assert_eq!(alabama.rape_legacy, Some(1369));
+        assert_eq!(alabama.rape_revised, None);
+        assert!((alabama.property_crime_rate - 3876.8).abs() < 0.1);
+
+        // Switching the measure keeps every record that reports the offense
+        let property = with_crime_measure(&records, Offense::PropertyTotal);
+        assert_eq!(property.len(), records.len());
+        assert_eq!(property[0].crime_rate, property[0].property_crime_rate);
+    }
+
+    #[test]
+    fn test_missing_offense_columns_are_warnings() {
+        use mass_incarceration_analysis::process_dataset;
+
+        // An export with only the original five columns still loads; each absent
+        // offense column is a Missing warning rather than a deserialize error
+        let path = std::env::temp_dir().join("mia_test_five_columns.csv");
+        std::fs::write(
+            &path,
+            "jurisdiction,year,prisoner_count,state_population,violent_crime_total\n\
+             ALABAMA,2001,24741,4468912,19582\n",
+        )
+        .unwrap();
+
+        let (records, invalid, issues) = process_dataset(path.to_str().unwrap()).unwrap();
+        assert_eq!((records.len(), invalid.len()), (1, 0));
+        assert_eq!(records[0].robbery, 0);
+        assert_eq!(records[0].rape_legacy, None);
+        assert!(records[0].validation_warnings.contains(&"murder_manslaughter".to_string()));
+        assert!(issues.iter().all(|i| i.field != "rape_legacy"));
+    }
+}
@@ -0,0 +1,37 @@
+use mass_incarceration_analysis::data_processing::process_dataset;
+use mass_incarceration_analysis::dataset_diff::diff_panels;
+use mass_incarceration_analysis::panel::Panel;
+
+#[test]
+fn test_diff_between_releases() {
+    let (records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let old = Panel::new(records.clone());
+    assert!(diff_panels(&old, &old).changes.is_empty());
+
+    // New release: one revised count, one dropped state-year, one new state-year
+    let mut updated: Vec<_> = records
+        .iter()
+        .filter(|r| !(r.jurisdiction == "MAINE" && r.year == 2003))
+        .cloned()
+        .collect();
+    let ohio = updated.iter_mut().find(|r| r.jurisdiction == "OHIO" && r.year == 2012).unwrap();
+    ohio.prisoner_count += 1000;
+    ohio.compute_rates();
+    let mut added = ohio.clone();
+    added.year = 2017;
+    updated.push(added);
+
+    let diff = diff_panels(&old, &Panel::new(updated));
+    assert_eq!(diff.removed, vec![("MAINE".to_string(), 2003)]);
+    assert_eq!(diff.added, vec![("OHIO".to_string(), 2017)]);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].field, "prisoner_count");
+    assert_eq!(diff.changes[0].diff, Some(1000.0));
+    assert_eq!(diff.changes_per_field["prisoner_count"], 1);
+
+    assert_eq!(diff.rate_movements.len(), 1);
+    let movement = &diff.rate_movements[0];
+    assert_eq!((movement.jurisdiction.as_str(), movement.year, movement.field.as_str()), ("OHIO", 2012, "incarceration_rate"));
+    assert!(movement.diff.unwrap() > 0.0);
+}
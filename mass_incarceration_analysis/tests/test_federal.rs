@@ -0,0 +1,61 @@
+use mass_incarceration_analysis::data_processing::{process_dataset, CleanRecord};
+use mass_incarceration_analysis::federal::{allocate_federal_by_population, load_federal_series, national_totals, FederalRecord};
+use mass_incarceration_analysis::rates::{apply_rate_spec, Denominator, RateScale, RateSpec};
+
+#[test]
+fn test_federal_rows_are_a_separate_series() {
+    let (records, invalid, issues) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    assert!(records.iter().all(|r| r.jurisdiction != "FEDERAL"));
+    assert!(invalid.iter().all(|r| r.jurisdiction != "FEDERAL"));
+    assert!(issues.iter().all(|i| i.jurisdiction != "FEDERAL"));
+
+    let federal = load_federal_series("crime_and_incarceration_by_state.csv").unwrap();
+    assert_eq!(federal.len(), 16);
+    assert_eq!(federal[0].year, 2001);
+    assert_eq!(federal[0].prisoner_count, 149_852);
+
+    let totals = national_totals(&records, &federal);
+    let first = totals[0];
+    assert_eq!(first.federal_prisoners, 149_852);
+    assert_eq!(first.total_prisoners, first.state_prisoners + first.federal_prisoners);
+}
+
+fn state(jurisdiction: &str, prisoner_count: u32, state_population: u32) -> CleanRecord {
+    CleanRecord {
+        jurisdiction: jurisdiction.to_string(),
+        year: 2010,
+        prisoner_count,
+        state_population,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_allocation_preserves_federal_total() {
+    let records = vec![state("OHIO", 5_000, 1_000_000), state("IOWA", 2_000, 3_000_000)];
+    let federal = [FederalRecord { year: 2010, prisoner_count: 1_000 }];
+    let allocated = allocate_federal_by_population(&records, &federal);
+
+    // Shares follow population: a quarter to Ohio, three quarters to Iowa
+    assert_eq!(allocated[0].prisoner_count, 5_250);
+    assert_eq!(allocated[1].prisoner_count, 2_750);
+    assert!((allocated[0].incarceration_rate - 525.0).abs() < 1e-3);
+
+    // Years without a federal count are left as they are
+    let other_year = allocate_federal_by_population(&records, &[FederalRecord { year: 2011, prisoner_count: 1_000 }]);
+    assert_eq!(other_year[0].prisoner_count, 5_000);
+}
+
+#[test]
+fn test_national_rate_follows_rate_spec() {
+    let records = vec![state("OHIO", 5_000, 1_000_000), state("IOWA", 2_000, 1_000_000)];
+    let federal = [FederalRecord { year: 2010, prisoner_count: 1_000 }];
+
+    let per_1k = apply_rate_spec(&records, &RateSpec::new(RateScale::PerThousand, Denominator::TotalPopulation));
+    let totals = national_totals(&per_1k, &federal);
+    assert_eq!(totals[0].total_prisoners, 8_000);
+    assert!((totals[0].incarceration_rate - 4.0).abs() < 1e-4);
+
+    // The default spec is per 100k
+    assert!((national_totals(&records, &federal)[0].incarceration_rate - 400.0).abs() < 1e-2);
+}
@@ -0,0 +1,79 @@
+use mass_incarceration_analysis::data_processing::CleanRecord;
+use mass_incarceration_analysis::harmonization::{harmonize_violent_crime, overlap_ratios, RapeHarmonization};
+
+// Published totals count the revised rape figure where one is reported
+fn record(year: u32, rape_legacy: Option<u32>, rape_revised: Option<u32>) -> CleanRecord {
+    let mut r = CleanRecord {
+        jurisdiction: "ALABAMA".to_string(),
+        year,
+        state_population: 4_800_000,
+        murder_manslaughter: 300,
+        rape_legacy,
+        rape_revised,
+        robbery: 4_000,
+        agg_assault: 12_000,
+        ..Default::default()
+    };
+    r.violent_crime_total = 300 + rape_revised.or(rape_legacy).unwrap() + 4_000 + 12_000;
+    r.compute_rates();
+    r
+}
+
+#[test]
+fn test_harmonized_series_use_one_definition() {
+    let records = vec![
+        record(2001, Some(1_000), None),
+        record(2012, Some(1_100), None),
+        record(2013, Some(1_200), Some(1_800)),
+        record(2014, None, Some(1_900)),
+    ];
+    let ratios = overlap_ratios(&records);
+    // The revised definition is broader, so it counts more offenses
+    assert!(ratios.pooled > 1.0);
+    assert!((ratios.for_state("Alabama") - 1.5).abs() < 1e-9);
+
+    let find = |rs: &[CleanRecord], year: u32| rs.iter().find(|r| r.year == year).unwrap().clone();
+
+    // Legacy method: 2013 uses the legacy count instead of the published revised one
+    let legacy = harmonize_violent_crime(&records, RapeHarmonization::LegacyWhereAvailable);
+    let alabama_2013 = find(&legacy, 2013);
+    assert_eq!(
+        alabama_2013.violent_crime_total,
+        alabama_2013.murder_manslaughter + alabama_2013.rape_legacy.unwrap() + alabama_2013.robbery + alabama_2013.agg_assault
+    );
+    // ... and 2014 is scaled down to the legacy definition
+    assert_eq!(find(&legacy, 2014).violent_crime_total, 300 + 1_267 + 4_000 + 12_000);
+
+    // Back-cast: 2001 is scaled up from legacy to the revised definition
+    let backcast = harmonize_violent_crime(&records, RapeHarmonization::RevisedBackcast);
+    assert_eq!(find(&backcast, 2001).violent_crime_total, 300 + 1_500 + 4_000 + 12_000);
+    assert!(find(&backcast, 2001).crime_rate > find(&records, 2001).crime_rate);
+
+    // Flag: published totals, with the first revised year marked as a break
+    let flagged = harmonize_violent_crime(&records, RapeHarmonization::FlagChange);
+    assert_eq!(find(&flagged, 2013).violent_crime_total, find(&records, 2013).violent_crime_total);
+    assert!(find(&flagged, 2013).crime_reporting_change);
+    assert!(!find(&flagged, 2014).crime_reporting_change);
+}
+
+#[test]
+fn test_published_totals_keep_everything_but_the_rape_term() {
+    // A pre-2013 total that counts more than the four components
+    let mut published = record(2010, Some(1_000), None);
+    published.violent_crime_total += 250;
+    published.compute_rates();
+    // Robbery was unreadable and coerced to 0 by a validation warning
+    let mut warned = record(2011, Some(1_050), None);
+    warned.robbery = 0;
+    warned.validation_warnings.push("robbery".to_string());
+    let records = vec![published.clone(), warned.clone(), record(2013, Some(1_200), Some(1_800))];
+
+    let legacy = harmonize_violent_crime(&records, RapeHarmonization::LegacyWhereAvailable);
+    assert_eq!(legacy[0].violent_crime_total, published.violent_crime_total);
+    assert_eq!(legacy[0].crime_rate, published.crime_rate);
+
+    // Only the rape term is back-cast: 1,000 legacy becomes 1,500 revised
+    let backcast = harmonize_violent_crime(&records, RapeHarmonization::RevisedBackcast);
+    assert_eq!(backcast[0].violent_crime_total, published.violent_crime_total + 500);
+    assert_eq!(backcast[1].violent_crime_total, warned.violent_crime_total);
+}
@@ -0,0 +1,71 @@
+use mass_incarceration_analysis::calculations::perform_t_test;
+use mass_incarceration_analysis::hypothesis::{
+    mann_whitney_u, paired_by_year, paired_t_test, pooled_t_test, welch_t_test, wilcoxon_signed_rank,
+};
+use mass_incarceration_analysis::data_processing::CleanRecord;
+
+const A: [f64; 5] = [1.0, 2.0, 3.0, 4.0, 5.0];
+const B: [f64; 5] = [2.0, 4.0, 6.0, 8.0, 10.0];
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-3
+}
+
+#[test]
+fn test_t_tests_match_textbook_values() {
+    let pooled = pooled_t_test(&A, &B).unwrap();
+    assert!(close(pooled.statistic, -1.8974));
+    assert_eq!(pooled.df, Some(8.0));
+    assert!(close(pooled.p_value, 0.0943));
+    assert!(close(pooled.effect_size, -1.2));
+    assert!(pooled.ci.0 < -3.0 && pooled.ci.1 > -3.0);
+
+    // perform_t_test now uses the textbook standard error
+    let (t, p) = perform_t_test(&A, &B).unwrap();
+    assert!(close(t, pooled.statistic) && close(p, pooled.p_value));
+
+    let welch = welch_t_test(&A, &B).unwrap();
+    assert!(close(welch.statistic, -1.8974));
+    assert!(close(welch.df.unwrap(), 5.8824));
+    assert!(welch.p_value > pooled.p_value);
+
+    let paired = paired_t_test(&A, &B).unwrap();
+    assert!(close(paired.statistic, -4.2426));
+    assert_eq!(paired.df, Some(4.0));
+    assert!(close(paired.p_value, 0.0132));
+    assert!(close(paired.estimate, -3.0));
+}
+
+#[test]
+fn test_rank_tests() {
+    let mw = mann_whitney_u(&A, &B).unwrap();
+    assert_eq!(mw.statistic, 5.0);
+    assert!(close(mw.effect_size, -0.6));
+    assert!(close(mw.p_value, 0.1413));
+    assert!(mw.ci.0 <= mw.estimate && mw.estimate <= mw.ci.1);
+
+    let wilcoxon = wilcoxon_signed_rank(&A, &B).unwrap();
+    assert_eq!(wilcoxon.statistic, 0.0);
+    assert!(close(wilcoxon.effect_size, -1.0));
+    assert!(close(wilcoxon.p_value, 0.0590));
+    assert!(close(wilcoxon.estimate, -3.0));
+
+    // A NaN rate (e.g. a zero population) is an error, not a panic
+    let with_nan = [1.0, f64::NAN, 3.0, 4.0, 5.0];
+    assert!(mann_whitney_u(&with_nan, &B).is_err());
+    assert!(wilcoxon_signed_rank(&A, &with_nan).is_err());
+}
+
+#[test]
+fn test_pairing_by_year() {
+    let record = |year: u32, crime_rate: f32| CleanRecord {
+        year,
+        crime_rate,
+        ..Default::default()
+    };
+    let a = vec![record(2003, 3.0), record(2001, 1.0), record(2002, 2.0)];
+    let b = vec![record(2001, 10.0), record(2003, 30.0)];
+    let (x, y) = paired_by_year(&a, &b, |r| r.crime_rate as f64);
+    assert_eq!(x, vec![1.0, 3.0]);
+    assert_eq!(y, vec![10.0, 30.0]);
+}
@@ -0,0 +1,95 @@
+use mass_incarceration_analysis::data_processing::{CleanRecord, DirtyRecord};
+use mass_incarceration_analysis::imputation::{exclude_imputed, impute_missing, ImputationStrategy};
+use mass_incarceration_analysis::panel::Panel;
+use mass_incarceration_analysis::rates::{apply_rate_spec, Denominator, RateScale, RateSpec};
+
+fn record(state: &str, year: u32, prisoner_count: u32, state_population: u32) -> CleanRecord {
+    let mut r = CleanRecord {
+        jurisdiction: state.to_string(),
+        year,
+        prisoner_count,
+        state_population,
+        violent_crime_total: state_population / 250,
+        robbery: state_population / 1_000,
+        ..Default::default()
+    };
+    r.compute_rates();
+    r
+}
+
+#[test]
+fn test_imputation_fills_new_york_2015() {
+    // New York 2015 was rejected for missing crime data but reported its custody count
+    let records = vec![
+        record("NEW YORK", 2013, 53_000, 19_600_000),
+        record("NEW YORK", 2014, 52_500, 19_700_000),
+        record("NEW YORK", 2016, 51_000, 19_750_000),
+        record("OHIO", 2013, 50_000, 11_570_000),
+        record("OHIO", 2014, 50_500, 11_590_000),
+        record("OHIO", 2015, 51_000, 11_610_000),
+        record("OHIO", 2016, 51_500, 11_630_000),
+    ];
+    let invalid = vec![DirtyRecord {
+        jurisdiction: "New York".to_string(),
+        year: "2015".to_string(),
+        prisoner_count: "51,485".to_string(),
+        ..Default::default()
+    }];
+    assert!(!Panel::new(records.clone()).is_balanced());
+
+    for strategy in [
+        ImputationStrategy::Linear,
+        ImputationStrategy::CarryForward,
+        ImputationStrategy::PopulationInterpolation,
+        ImputationStrategy::ModelBased,
+    ] {
+        let filled = Panel::new(impute_missing(&records, &invalid, strategy));
+        assert!(filled.is_balanced(), "{:?}", strategy);
+
+        let ny = filled.get("New York", 2015).unwrap();
+        assert!(ny.is_imputed());
+        assert!(ny.imputed.contains(&"state_population".to_string()));
+        // The custody count was reported, so it is not imputed
+        assert_eq!(ny.prisoner_count, 51485);
+        assert!(!ny.imputed.contains(&"prisoner_count".to_string()));
+
+        let (before, after) = (filled.get("New York", 2014).unwrap(), filled.get("New York", 2016).unwrap());
+        let low = before.state_population.min(after.state_population);
+        let high = before.state_population.max(after.state_population);
+        if strategy != ImputationStrategy::ModelBased {
+            assert!(ny.state_population >= low && ny.state_population <= high, "{:?}", strategy);
+        }
+
+        assert_eq!(exclude_imputed(&filled).len(), records.len());
+    }
+}
+
+#[test]
+fn test_imputation_keeps_adult_population_denominator() {
+    let record = |jurisdiction: &str, year: u32, adults: f64| {
+        let mut r = CleanRecord {
+            jurisdiction: jurisdiction.to_string(),
+            year,
+            prisoner_count: 50_000,
+            state_population: 11_500_000,
+            ..Default::default()
+        };
+        r.covariates.insert("adult_population".to_string(), adults);
+        r
+    };
+    let spec = RateSpec::new(RateScale::PerHundredThousand, Denominator::AdultPopulation);
+    // Iowa reports 2011, so Ohio's 2011 is a missing cell
+    let records = [
+        record("OHIO", 2010, 8_000_000.0),
+        record("OHIO", 2012, 9_000_000.0),
+        record("IOWA", 2011, 2_400_000.0),
+    ];
+    let records = apply_rate_spec(&records, &spec);
+
+    let filled = Panel::new(impute_missing(&records, &[], ImputationStrategy::Linear));
+    let ohio = filled.get("Ohio", 2011).unwrap();
+    assert_eq!(ohio.rate_spec, spec);
+    assert_eq!(ohio.covariate("adult_population"), Some(8_500_000.0));
+    assert!(ohio.imputed.contains(&"adult_population".to_string()));
+    assert!((ohio.incarceration_rate - 50_000.0 / 8_500_000.0 * 100_000.0).abs() < 1e-2);
+}
@@ -0,0 +1,50 @@
+use mass_incarceration_analysis::data_processing::CleanRecord;
+use mass_incarceration_analysis::jails::{adjust_for_jails, jail_adjustment_report, JailAdjustment};
+
+fn record(state: &str, year: u32, prisoner_count: u32, violent_crime_total: u32, includes_jails: bool) -> CleanRecord {
+    let mut r = CleanRecord {
+        jurisdiction: state.to_string(),
+        year,
+        prisoner_count,
+        state_population: 1_000_000,
+        violent_crime_total,
+        includes_jails,
+        ..Default::default()
+    };
+    r.compute_rates();
+    r
+}
+
+#[test]
+fn test_jail_adjustment_modes() {
+    let records = vec![
+        record("ALASKA", 2001, 6_000, 4_500, true),
+        record("ALASKA", 2002, 6_200, 4_700, true),
+        record("VERMONT", 2001, 2_500, 1_200, true),
+        record("VERMONT", 2002, 2_600, 1_300, true),
+        record("OHIO", 2001, 4_000, 3_400, false),
+        record("OHIO", 2002, 4_100, 3_300, false),
+        record("IOWA", 2001, 3_000, 2_800, false),
+        record("IOWA", 2002, 3_200, 2_900, false),
+        record("TEXAS", 2001, 7_000, 5_500, false),
+        record("TEXAS", 2002, 7_300, 5_300, false),
+    ];
+    let with_jails = records.iter().filter(|r| r.includes_jails).count();
+    assert_eq!(with_jails, 4);
+
+    let excluded = adjust_for_jails(&records, JailAdjustment::Exclude);
+    assert_eq!(excluded[0].len(), records.len() - with_jails);
+
+    let separate = adjust_for_jails(&records, JailAdjustment::Separate);
+    assert_eq!(separate[1].len(), with_jails);
+
+    let scaled = adjust_for_jails(&records, JailAdjustment::Scale(0.5));
+    let alaska = |rs: &[CleanRecord]| rs.iter().find(|r| r.jurisdiction == "ALASKA" && r.year == 2001).unwrap().incarceration_rate;
+    assert!((alaska(&scaled[0]) - alaska(&records) * 0.5).abs() < 1.0);
+    // Prison-only states are untouched
+    assert_eq!(scaled[0][4].incarceration_rate, records[4].incarceration_rate);
+
+    let report = jail_adjustment_report(&records, JailAdjustment::Exclude);
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[1].records, records.len() - with_jails);
+}
@@ -0,0 +1,89 @@
+use mass_incarceration_analysis::data_processing::CleanRecord;
+use mass_incarceration_analysis::lags::{distributed_lag, granger_both_directions, granger_tests, LagStructure};
+use std::collections::HashMap;
+
+const STATES: [&str; 12] = [
+    "ALABAMA", "ARIZONA", "COLORADO", "GEORGIA", "IOWA", "KANSAS", "MAINE", "NEVADA", "OHIO", "OREGON", "TEXAS", "UTAH",
+];
+
+// Incarceration and crime rates that move independently of each other
+fn records() -> Vec<CleanRecord> {
+    let mut records = Vec::new();
+    for (s, state) in STATES.iter().enumerate() {
+        for year in 2001..=2016 {
+            let i = records.len() as f32;
+            records.push(CleanRecord {
+                jurisdiction: state.to_string(),
+                year,
+                incarceration_rate: 400.0 + 20.0 * s as f32 + 60.0 * (i * 2.17).sin() + 40.0 * (i * 0.71).cos(),
+                crime_rate: 350.0 + 60.0 * (i * 1.37).cos(),
+                ..Default::default()
+            });
+        }
+    }
+    records
+}
+
+// y reacts to last year's x only
+fn simulated() -> Vec<CleanRecord> {
+    let records = records();
+    let x: HashMap<(String, u32), f64> =
+        records.iter().map(|r| ((r.jurisdiction.clone(), r.year), r.incarceration_rate as f64)).collect();
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut r)| {
+            r.covariates.insert("x".to_string(), r.incarceration_rate as f64);
+            if let Some(previous) = x.get(&(r.jurisdiction.clone(), r.year - 1)) {
+                r.covariates.insert("y".to_string(), 0.5 * previous + (i as f64 * 1.3).sin() * 10.0);
+            }
+            r
+        })
+        .collect()
+}
+
+#[test]
+fn test_distributed_lag_recovers_delayed_effect() {
+    let lags = LagStructure { predictor_lags: vec![0, 1, 2], response_lags: 0 };
+    // Two years cannot carry two lags, so this state is skipped, not fitted
+    let mut records = simulated();
+    for year in [2015, 2016] {
+        let mut short = CleanRecord { jurisdiction: "PUERTO RICO".to_string(), year, ..Default::default() };
+        short.covariates.insert("x".to_string(), 100.0);
+        short.covariates.insert("y".to_string(), 50.0);
+        records.push(short);
+    }
+    let results = distributed_lag(&records, "y", "x", &lags).unwrap();
+    let skipped: Vec<&str> = results.skipped.iter().map(|(state, _)| state.as_str()).collect();
+    assert_eq!(skipped, ["PUERTO RICO"]);
+    assert!(results.skipped[0].1.contains("complete years"));
+    let fits = &results.fits;
+
+    let pooled = fits.last().unwrap();
+    assert!(pooled.jurisdiction.is_none());
+    assert!((pooled.fit.coefficient("x_lag1").unwrap().estimate - 0.5).abs() < 0.05);
+    assert!(pooled.fit.coefficient("x").unwrap().estimate.abs() < 0.05);
+    assert!((pooled.long_run_effect - 0.5).abs() < 0.05);
+    assert_eq!(fits.iter().filter(|f| f.jurisdiction.is_some()).count(), STATES.len());
+}
+
+#[test]
+fn test_granger_tests() {
+    let tests = granger_tests(&simulated(), "x", "y", 1).unwrap().tests;
+    let pooled = tests.last().unwrap();
+    assert!(pooled.jurisdiction.is_none());
+    assert_eq!((pooled.df1, pooled.lags), (1, 1));
+    assert!(pooled.p_value < 0.001);
+
+    // Both directions: per state, then pooled, for each
+    let both = granger_both_directions(&records(), 2).unwrap();
+    assert!(both.skipped.is_empty());
+    let both = both.tests;
+    let pooled: Vec<_> = both.iter().filter(|t| t.jurisdiction.is_none()).collect();
+    assert_eq!(pooled.len(), 2);
+    assert_eq!((pooled[0].cause.as_str(), pooled[0].effect.as_str()), ("incarceration_rate", "crime_rate"));
+    assert_eq!((pooled[1].cause.as_str(), pooled[1].effect.as_str()), ("crime_rate", "incarceration_rate"));
+    assert!(both.iter().all(|t| t.f_statistic >= 0.0 && (0.0..=1.0).contains(&t.p_value)));
+
+    assert!(granger_tests(&records(), "crime_rate", "incarceration_rate", 0).is_err());
+}
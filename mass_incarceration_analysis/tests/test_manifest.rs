@@ -0,0 +1,42 @@
+use mass_incarceration_analysis::breaks::BreakHandling;
+use mass_incarceration_analysis::manifest::{hash_file, RunManifest, RunParameters};
+
+#[test]
+fn test_manifest_hashes_inputs_and_new_artifacts() {
+    let dir = std::env::temp_dir().join("mia_test_manifest");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("out")).unwrap();
+    let input = dir.join("input.csv");
+    std::fs::write(&input, "abc").unwrap();
+
+    let mut manifest = RunManifest::start(
+        &[input.to_str().unwrap()],
+        RunParameters {
+            graph_threshold: 50.0,
+            similarity_cutoff: 0.7,
+            k_core: 3,
+            states_compared: vec!["Arizona".to_string(), "Massachusetts".to_string()],
+            rate_spec: "per 100k total population".to_string(),
+            break_handling: BreakHandling::Exclude,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        manifest.inputs[0].sha256,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    std::fs::write(dir.join("out").join("plot.png"), "png").unwrap();
+    manifest.finish(dir.join("out").to_str().unwrap()).unwrap();
+    assert_eq!(manifest.artifacts.len(), 1);
+    assert!(manifest.artifacts[0].path.ends_with("plot.png"));
+    assert_eq!(manifest.artifacts[0].sha256, hash_file(&manifest.artifacts[0].path).unwrap().sha256);
+    assert!(manifest.finished_at >= manifest.started_at);
+
+    let path = dir.join("run_manifest.json");
+    manifest.write(path.to_str().unwrap()).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(json["parameters"]["graph_threshold"], 50.0);
+    assert_eq!(json["parameters"]["break_handling"], "Exclude");
+    assert_eq!(json["crate_version"], env!("CARGO_PKG_VERSION"));
+}
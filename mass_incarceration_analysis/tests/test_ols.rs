@@ -0,0 +1,113 @@
+use mass_incarceration_analysis::data_processing::{CleanRecord, Offense};
+use mass_incarceration_analysis::ols::{build_design_matrix, fit_design, fit_ols, DesignMatrix, OlsSolver, OlsSpec, Regressor};
+use ndarray::{array, Array2};
+
+// Two states per region over 2001-2016; incarceration follows both crime rates
+fn records() -> Vec<CleanRecord> {
+    let states = ["ALABAMA", "TEXAS", "OHIO", "IOWA", "MAINE", "NEW YORK", "OREGON", "UTAH"];
+    let mut records = Vec::new();
+    for (s, state) in states.iter().enumerate() {
+        for year in 2001..=2016 {
+            let i = (s * 16 + year as usize - 2001) as f32;
+            let crime_rate = 400.0 + 150.0 * (i * 0.37).sin() + 20.0 * s as f32;
+            let property_crime_rate = 2_500.0 + 600.0 * (i * 0.83).cos();
+            records.push(CleanRecord {
+                jurisdiction: state.to_string(),
+                year,
+                crime_rate,
+                property_crime_rate,
+                incarceration_rate: 150.0 + 0.6 * crime_rate + 0.05 * property_crime_rate + 15.0 * (i * 1.9).sin(),
+                ..Default::default()
+            });
+        }
+    }
+    records
+}
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-4
+}
+
+#[test]
+fn test_ols_matches_textbook_line() {
+    let mut sample: Vec<CleanRecord> = records().into_iter().take(5).collect();
+    for (record, (x, y)) in sample.iter_mut().zip([(1.0, 2.0), (2.0, 4.0), (3.0, 5.0), (4.0, 4.0), (5.0, 5.0)]) {
+        record.covariates.insert("x".to_string(), x);
+        record.covariates.insert("y".to_string(), y);
+    }
+
+    let fit = fit_ols(&sample, &OlsSpec::new("y", vec![Regressor::feature("x")])).unwrap();
+    let slope = fit.coefficient("x").unwrap();
+    assert!(close(slope.estimate, 0.6));
+    assert!(close(fit.coefficient("intercept").unwrap().estimate, 2.2));
+    assert!(close(slope.std_error, 0.8_f64.sqrt() / 10.0_f64.sqrt()));
+    assert!(close(fit.r_squared, 0.6));
+    assert_eq!(slope.vif, Some(1.0));
+    assert!(slope.hc1_se > 0.0 && slope.hc3_se.unwrap() > 0.0);
+}
+
+#[test]
+fn test_design_matrix_and_solvers() {
+    let records = records();
+    let mut spec = OlsSpec::new(
+        "incarceration_rate",
+        vec![
+            Regressor::feature("crime_rate"),
+            Regressor::OffenseRate(Offense::PropertyTotal),
+            Regressor::Year,
+            Regressor::RegionDummies,
+            Regressor::interaction(Regressor::feature("crime_rate"), Regressor::RegionDummies),
+        ],
+    );
+
+    let design = build_design_matrix(&records, &spec).unwrap();
+    assert_eq!(design.names.len(), 10);
+    assert!(design.names.contains(&"property_crime_rate".to_string()));
+    assert!(design.names.contains(&"crime_rate:region_South".to_string()));
+    assert_eq!(design.x.nrows() + design.dropped, records.len());
+
+    let qr = fit_ols(&records, &spec).unwrap();
+    spec.solver = OlsSolver::Svd;
+    let svd = fit_ols(&records, &spec).unwrap();
+    for (a, b) in qr.coefficients.iter().zip(&svd.coefficients) {
+        assert!((a.estimate - b.estimate).abs() < 1e-6 * a.estimate.abs().max(1.0));
+    }
+    assert!(qr.coefficients.iter().skip(1).all(|c| c.vif.unwrap() >= 1.0));
+    assert!(qr.f_p_value < 0.05);
+
+    // The same column twice cannot be solved by either method
+    spec.regressors.push(Regressor::feature("crime_rate"));
+    assert!(fit_ols(&records, &spec).is_err());
+    spec.solver = OlsSolver::Qr;
+    assert!(fit_ols(&records, &spec).is_err());
+}
+
+#[test]
+fn test_fit_needs_more_rows_than_columns() {
+    let design = DesignMatrix {
+        names: vec!["intercept".to_string(), "x".to_string(), "z".to_string()],
+        x: Array2::from_shape_vec((2, 3), vec![1.0, 1.0, 2.0, 1.0, 2.0, 1.0]).unwrap(),
+        y: array![1.0, 2.0],
+        rows: vec![("OHIO".to_string(), 2010), ("OHIO".to_string(), 2011)],
+        dropped: 0,
+    };
+    let err = fit_design(&design, &OlsSpec::new("y", Vec::new())).unwrap_err();
+    assert_eq!(err.to_string(), "2 observations for 3 parameters");
+}
+
+#[test]
+fn test_hc3_is_undefined_at_leverage_one() {
+    // The dummy fits the first row exactly, so its leverage is 1
+    let design = DesignMatrix {
+        names: vec!["intercept".to_string(), "x".to_string(), "first".to_string()],
+        x: array![[1.0, 1.0, 1.0], [1.0, 2.0, 0.0], [1.0, 3.0, 0.0], [1.0, 4.0, 0.0], [1.0, 5.0, 0.0]],
+        y: array![7.0, 4.0, 5.0, 4.0, 5.0],
+        rows: (2001..=2005).map(|year| ("OHIO".to_string(), year)).collect(),
+        dropped: 0,
+    };
+    let fit = fit_design(&design, &OlsSpec::new("y", Vec::new())).unwrap();
+    for c in &fit.coefficients {
+        assert!(c.hc3_se.is_none() && c.hc3_p.is_none(), "{}", c.name);
+        assert!(c.hc1_se.is_finite());
+    }
+}
@@ -0,0 +1,44 @@
+use mass_incarceration_analysis::data_processing::{identify_outliers, process_dataset, CleanRecord};
+use mass_incarceration_analysis::panel::Panel;
+
+fn record(state: &str, year: u32, incarceration_rate: f32) -> CleanRecord {
+    CleanRecord {
+        jurisdiction: state.to_string(),
+        year,
+        incarceration_rate,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_panel_lookup_and_series() {
+    let panel = Panel::new(vec![
+        record("ARIZONA", 2002, 2.0),
+        record("Arizona", 2001, 1.0),
+        record("MAINE", 2001, 3.0),
+    ]);
+
+    assert_eq!(panel.states(), ["ARIZONA", "MAINE"]);
+    assert_eq!(panel.years(), [2001, 2002]);
+    assert_eq!(panel.get("AZ", 2002).unwrap().incarceration_rate, 2.0);
+    assert!(panel.get("Nevada", 2002).is_none());
+    assert_eq!(panel.get("arizona", 2002).unwrap().incarceration_rate, 2.0);
+
+    // Series come back sorted by year regardless of input order
+    let years: Vec<u32> = panel.series("Arizona").iter().map(|r| r.year).collect();
+    assert_eq!(years, vec![2001, 2002]);
+    assert_eq!(panel.cross_section(2001).len(), 2);
+
+    assert!(!panel.is_balanced());
+    assert_eq!(panel.missing_cells(), vec![("MAINE".to_string(), 2002)]);
+}
+
+#[test]
+fn test_panel_feeds_existing_analyses() {
+    let (records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    let panel = Panel::new(records.clone());
+
+    assert_eq!(panel.len(), records.len());
+    assert_eq!(panel.series("Massachusetts").len(), 16);
+    assert_eq!(identify_outliers(&panel).len(), identify_outliers(&records).len());
+}
@@ -0,0 +1,70 @@
+use mass_incarceration_analysis::data_processing::CleanRecord;
+use mass_incarceration_analysis::ols::{OlsSpec, Regressor};
+use mass_incarceration_analysis::panel_models::{fit_panel, hausman_test, PanelEstimator};
+
+const STATES: [&str; 20] = [
+    "ALABAMA", "ARIZONA", "CALIFORNIA", "COLORADO", "FLORIDA", "GEORGIA", "ILLINOIS", "IOWA", "KANSAS", "MAINE",
+    "MICHIGAN", "NEVADA", "NEW YORK", "OHIO", "OREGON", "TEXAS", "UTAH", "VERMONT", "VIRGINIA", "WISCONSIN",
+];
+
+// y = 2x + state effect + noise, where the state effect partly follows the
+// state's mean x, so pooled and random-effects slopes are biased
+fn simulated() -> Vec<CleanRecord> {
+    let mut records = Vec::new();
+    for (s, state) in STATES.iter().enumerate() {
+        let level = 300.0 + 150.0 * (s as f64 * 0.9).sin();
+        let effect = level + 40.0 * (s as f64 * 2.3).sin();
+        for year in 2001..=2016 {
+            let i = records.len() as f64;
+            let x = level + 50.0 * (i * 0.61).sin();
+            let noise = (i * 1.7).sin() * 60.0;
+            let mut r = CleanRecord { jurisdiction: state.to_string(), year, ..Default::default() };
+            r.covariates.insert("x".to_string(), x);
+            r.covariates.insert("y".to_string(), 2.0 * x + effect + noise);
+            records.push(r);
+        }
+    }
+    records
+}
+
+#[test]
+fn test_fixed_effects_remove_state_heterogeneity() {
+    let records = simulated();
+    let spec = OlsSpec::new("y", vec![Regressor::feature("x")]);
+
+    let within = fit_panel(&records, &spec, PanelEstimator::Within).unwrap();
+    let slope = within.coefficient("x").unwrap();
+    assert!((slope.estimate - 2.0).abs() < 0.1);
+    assert_eq!(within.states, STATES.len());
+    assert!(slope.cluster_se > 0.0 && slope.ci.0 < 2.0 && slope.ci.1 > 2.0);
+
+    let two_way = fit_panel(&records, &spec, PanelEstimator::TwoWay).unwrap();
+    assert_eq!(two_way.coefficients.len(), 1);
+    assert!((two_way.coefficient("x").unwrap().estimate - 2.0).abs() < 0.1);
+
+    let random = fit_panel(&records, &spec, PanelEstimator::RandomEffects).unwrap();
+    assert!(random.coefficient("intercept").is_some());
+    let (min, max) = random.theta.unwrap();
+    assert!(min > 0.0 && max < 1.0);
+    assert!(random.coefficient("x").unwrap().estimate > 2.3);
+
+    let hausman = hausman_test(&within, &random).unwrap();
+    assert_eq!(hausman.df, 1);
+    assert!(hausman.p_value < 0.01);
+}
+
+#[test]
+fn test_time_invariant_regressors_are_rejected() {
+    let records = simulated();
+    let spec = OlsSpec::new("y", vec![Regressor::feature("x"), Regressor::RegionDummies]);
+    assert!(fit_panel(&records, &spec, PanelEstimator::Within).is_err());
+
+    // Random effects can still estimate them
+    let random = fit_panel(&records, &spec, PanelEstimator::RandomEffects).unwrap();
+    assert!(random.coefficient("region_South").is_some());
+
+    // A linear trend is absorbed by the year effects
+    let trend = OlsSpec::new("y", vec![Regressor::feature("x"), Regressor::Year]);
+    assert!(fit_panel(&records, &trend, PanelEstimator::Within).is_ok());
+    assert!(fit_panel(&records, &trend, PanelEstimator::TwoWay).is_err());
+}
@@ -0,0 +1,65 @@
+use mass_incarceration_analysis::data_processing::process_dataset;
+use mass_incarceration_analysis::polars_pipeline::{
+    frame_to_records, records_to_frame, region_averages, scan_combined, with_rates, yearly_averages,
+};
+use mass_incarceration_analysis::rates::{Denominator, RateScale, RateSpec};
+
+const DATA: &str = "crime_and_incarceration_by_state.csv";
+
+#[test]
+fn test_lazy_rates_match_process_dataset() {
+    let (records, _, _) = process_dataset(DATA).unwrap();
+    let df = with_rates(scan_combined(DATA).unwrap(), &RateSpec::default()).unwrap().collect().unwrap();
+    assert_eq!(df.height(), records.len());
+
+    let from_frame = frame_to_records(&df).unwrap();
+    let find = |rs: &[mass_incarceration_analysis::CleanRecord]| {
+        rs.iter().find(|r| r.jurisdiction == "TEXAS" && r.year == 2010).cloned().unwrap()
+    };
+    let (expected, actual) = (find(&records), find(&from_frame));
+    assert_eq!(expected.prisoner_count, actual.prisoner_count);
+    assert_eq!(expected.rape_legacy, actual.rape_legacy);
+    assert!((expected.incarceration_rate - actual.incarceration_rate).abs() < 1e-3);
+    assert!((expected.crime_rate - actual.crime_rate).abs() < 1e-3);
+}
+
+#[test]
+fn test_records_round_trip_through_frame() {
+    let (records, _, _) = process_dataset(DATA).unwrap();
+    let df = records_to_frame(&records).unwrap();
+    assert_eq!(df.height(), records.len());
+
+    let back = frame_to_records(&df).unwrap();
+    assert_eq!(back.len(), records.len());
+    for (a, b) in records.iter().zip(&back) {
+        assert_eq!((&a.jurisdiction, a.year), (&b.jurisdiction, b.year));
+        assert_eq!(a.violent_crime_total, b.violent_crime_total);
+        assert_eq!(a.rape_revised, b.rape_revised);
+        assert_eq!(a.includes_jails, b.includes_jails);
+        assert_eq!(a.crime_reporting_change, b.crime_reporting_change);
+    }
+}
+
+#[test]
+fn test_group_by_year_and_region() {
+    let lf = with_rates(scan_combined(DATA).unwrap(), &RateSpec::default()).unwrap();
+    let yearly = yearly_averages(lf.clone()).collect().unwrap();
+    assert_eq!(yearly.height(), 16); // 2001-2016
+
+    let regional = region_averages(lf).unwrap().collect().unwrap();
+    let regions = regional.column("region").unwrap().n_unique().unwrap();
+    assert_eq!(regions, 4);
+}
+
+#[test]
+fn test_lazy_rates_follow_rate_spec() {
+    let per_1k = RateSpec::new(RateScale::PerThousand, Denominator::TotalPopulation);
+    let df = with_rates(scan_combined(DATA).unwrap(), &per_1k).unwrap().collect().unwrap();
+    let default = with_rates(scan_combined(DATA).unwrap(), &RateSpec::default()).unwrap().collect().unwrap();
+    let rate = |df: &polars::prelude::DataFrame| df.column("incarceration_rate").unwrap().f64().unwrap().get(0).unwrap();
+    assert!((rate(&default) / 100.0 - rate(&df)).abs() < 1e-9);
+
+    // Only total population is available as a denominator
+    let adult = RateSpec::new(RateScale::PerHundredThousand, Denominator::AdultPopulation);
+    assert!(with_rates(scan_combined(DATA).unwrap(), &adult).is_err());
+}
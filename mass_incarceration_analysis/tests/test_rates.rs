@@ -0,0 +1,68 @@
+use mass_incarceration_analysis::covariates::{attach_covariates, CovariateTable};
+use mass_incarceration_analysis::data_processing::CleanRecord;
+use mass_incarceration_analysis::harmonization::{harmonize_violent_crime, RapeHarmonization};
+use mass_incarceration_analysis::rates::{apply_rate_spec, Denominator, RateScale, RateSpec};
+
+fn record(state: &str, year: u32) -> CleanRecord {
+    let mut r = CleanRecord {
+        jurisdiction: state.to_string(),
+        year,
+        prisoner_count: 50_000,
+        state_population: 11_500_000,
+        violent_crime_total: 35_000,
+        murder_manslaughter: 600,
+        rape_legacy: Some(4_000),
+        robbery: 12_000,
+        agg_assault: 18_400,
+        ..Default::default()
+    };
+    r.compute_rates();
+    r
+}
+
+#[test]
+fn test_rate_scale_rebases_every_rate() {
+    let records = vec![record("OHIO", 2010), record("TEXAS", 2010)];
+    let per_1k = apply_rate_spec(&records, &RateSpec::new(RateScale::PerThousand, Denominator::TotalPopulation));
+    assert_eq!(per_1k.len(), records.len());
+
+    for (a, b) in records.iter().zip(&per_1k) {
+        assert!((a.incarceration_rate / 100.0 - b.incarceration_rate).abs() < 1e-3);
+        assert!((a.robbery_rate / 100.0 - b.robbery_rate).abs() < 1e-3);
+    }
+
+    // Analyses that recompute a rate keep the record's spec
+    let harmonized = harmonize_violent_crime(&per_1k, RapeHarmonization::RevisedBackcast);
+    let ohio = harmonized.iter().find(|r| r.jurisdiction == "OHIO" && r.year == 2010).unwrap();
+    assert!((ohio.crime_rate - ohio.violent_crime_total as f32 / ohio.state_population as f32 * 1_000.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_covariate_denominator() {
+    let records = vec![record("TEXAS", 2010), record("TEXAS", 2011)];
+    let mut table = CovariateTable {
+        names: vec!["adult_population".to_string()],
+        ..Default::default()
+    };
+    table.values.insert(("TEXAS".to_string(), 2010), vec![Some(18_000_000.0)]);
+
+    let spec = RateSpec::new(RateScale::PerHundredThousand, Denominator::AdultPopulation);
+    assert_eq!(spec.label(), "per 100k adult population");
+    let adult = apply_rate_spec(&attach_covariates(&records, &table), &spec);
+    assert_eq!(adult.len(), 1); // Only Texas 2010 has the denominator
+    let texas = &adult[0];
+    assert_eq!(texas.year, 2010);
+    assert!((texas.incarceration_rate - texas.prisoner_count as f32 / 18_000_000.0 * 100_000.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_rate_spec_labels_parse_back() {
+    for spec in [
+        RateSpec::default(),
+        RateSpec::new(RateScale::PerThousand, Denominator::AdultPopulation),
+        RateSpec::new(RateScale::PerTenThousand, Denominator::Covariate("households".to_string())),
+    ] {
+        assert_eq!(RateSpec::from_label(&spec.label()), Some(spec));
+    }
+    assert_eq!(RateSpec::from_label("per 5k total population"), None);
+}
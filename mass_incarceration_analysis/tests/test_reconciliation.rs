@@ -0,0 +1,33 @@
+use mass_incarceration_analysis::reconciliation::reconcile_files;
+
+const CUSTODY: &str = "prison_custody_by_state.csv";
+const UCR: &str = "ucr_by_state.csv";
+const COMBINED: &str = "crime_and_incarceration_by_state.csv";
+
+#[test]
+fn test_shipped_files_reconcile() {
+    let report = reconcile_files(CUSTODY, UCR, COMBINED, 0.0).unwrap();
+    assert!(report.mismatches.is_empty(), "{:?}", &report.mismatches[..report.mismatches.len().min(5)]);
+    assert!(report.only_in_combined.is_empty());
+    assert!(report.only_in_custody.is_empty());
+    assert!(!report.only_in_ucr.is_empty()); // DC and 2017 have no custody counts
+
+    let prisoners = report.summary.iter().find(|s| s.field == "prisoner_count").unwrap();
+    assert_eq!(prisoners.compared, 16 * 51);
+}
+
+#[test]
+fn test_edited_combined_value_is_reported() {
+    let original = std::fs::read_to_string(COMBINED).unwrap();
+    let edited = original.replacen("ALABAMA,False,2001,24741,", "ALABAMA,False,2001,25000,", 1);
+    assert_ne!(original, edited);
+    let path = std::env::temp_dir().join("mia_test_reconcile_combined.csv");
+    std::fs::write(&path, edited).unwrap();
+
+    let report = reconcile_files(CUSTODY, UCR, path.to_str().unwrap(), 0.0).unwrap();
+    assert_eq!(report.mismatches.len(), 1);
+    let m = &report.mismatches[0];
+    assert_eq!((m.jurisdiction.as_str(), m.year, m.field.as_str()), ("ALABAMA", 2001, "prisoner_count"));
+    assert_eq!(m.abs_diff, Some(259.0));
+    assert!((m.rel_diff.unwrap() - 259.0 / 24741.0).abs() < 1e-12);
+}
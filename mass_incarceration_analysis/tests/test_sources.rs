@@ -0,0 +1,41 @@
+use mass_incarceration_analysis::sources::load_prison_custody;
+
+#[test]
+fn test_load_prison_custody_reshapes_wide_table() {
+    let records = load_prison_custody("prison_custody_by_state.csv").unwrap();
+
+    // 51 jurisdictions (50 states + Federal) x 16 years
+    assert_eq!(records.len(), 51 * 16);
+
+    let federal = records
+        .iter()
+        .find(|r| r.jurisdiction == "FEDERAL" && r.year == 2001)
+        .unwrap();
+    assert_eq!(federal.prisoner_count, 149_852);
+    assert!(!federal.includes_jails);
+
+    let alaska = records
+        .iter()
+        .find(|r| r.jurisdiction == "ALASKA" && r.year == 2016)
+        .unwrap();
+    assert!(alaska.includes_jails);
+}
+
+#[test]
+fn test_rebuild_matches_combined_file() {
+    use mass_incarceration_analysis::sources::rebuild_combined_dataset;
+
+    let output = std::env::temp_dir().join("rebuilt_crime_and_incarceration.csv");
+    let output = output.to_str().unwrap();
+    let join = rebuild_combined_dataset("prison_custody_by_state.csv", "ucr_by_state.csv", output).unwrap();
+
+    // Federal has no UCR data, and neither does New York in 2015;
+    // DC ("DC" in the UCR file), Puerto Rico and 2017 have no custody counts
+    assert_eq!(join.unmatched_custody.len(), 17);
+    assert!(join.unmatched_custody.contains(&("NEW YORK".to_string(), 2015)));
+    assert!(join.unmatched_ucr.iter().any(|(state, _)| state == "DISTRICT OF COLUMBIA"));
+
+    let rebuilt = std::fs::read_to_string(output).unwrap();
+    let original = std::fs::read_to_string("crime_and_incarceration_by_state.csv").unwrap();
+    assert_eq!(rebuilt, original);
+}
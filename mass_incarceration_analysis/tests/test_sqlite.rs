@@ -0,0 +1,108 @@
+use mass_incarceration_analysis::data_processing::{CleanRecord, DirtyRecord};
+use mass_incarceration_analysis::rates::{apply_rate_spec, Denominator, RateScale, RateSpec};
+use mass_incarceration_analysis::sqlite::{
+    load_panel, open_database, write_centrality, write_invalid_records, write_outliers, write_panel,
+    write_regression,
+};
+
+fn record(year: u32, rape_legacy: Option<u32>, rape_revised: Option<u32>) -> CleanRecord {
+    let mut r = CleanRecord {
+        jurisdiction: "OHIO".to_string(),
+        year,
+        prisoner_count: 50_000 + year,
+        state_population: 11_500_000,
+        violent_crime_total: 35_000,
+        rape_legacy,
+        rape_revised,
+        ..Default::default()
+    };
+    r.compute_rates();
+    r
+}
+
+#[test]
+fn test_panel_round_trips_through_sqlite() {
+    let records = vec![record(2013, Some(3_000), Some(4_500)), record(2014, None, Some(4_600))];
+    let invalid = vec![DirtyRecord {
+        jurisdiction: "New York".to_string(),
+        year: "2015".to_string(),
+        prisoner_count: "51,485".to_string(),
+        ..Default::default()
+    }];
+    let path = std::env::temp_dir().join("mia_test_panel.sqlite");
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_str().unwrap();
+
+    let mut db = open_database(path).unwrap();
+    write_panel(&mut db, &records).unwrap();
+    write_invalid_records(&mut db, &invalid).unwrap();
+    write_regression(&mut db, "all states", 0.5, 100.0, records.len()).unwrap();
+    write_centrality(&mut db, &[("TEXAS".to_string(), 3)]).unwrap();
+
+    let invalid_rows: i64 = db.query_row("SELECT COUNT(*) FROM invalid_records", [], |r| r.get(0)).unwrap();
+    assert_eq!(invalid_rows as usize, invalid.len());
+    let slope: f64 = db
+        .query_row("SELECT slope FROM regressions WHERE label = 'all states'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(slope, 0.5);
+
+    let panel = load_panel(path).unwrap();
+    assert_eq!(panel.len(), records.len());
+    let (a, b) = (&records[1], panel.get("Ohio", 2014).unwrap());
+    assert_eq!((a.prisoner_count, a.rape_legacy, a.rape_revised), (b.prisoner_count, b.rape_legacy, b.rape_revised));
+    assert!((a.crime_rate - b.crime_rate).abs() < 1e-3);
+}
+
+#[test]
+fn test_load_panel_keeps_rate_spec() {
+    let mut record = CleanRecord {
+        jurisdiction: "OHIO".to_string(),
+        year: 2010,
+        prisoner_count: 50_000,
+        state_population: 11_500_000,
+        violent_crime_total: 35_000,
+        ..Default::default()
+    };
+    record.covariates.insert("adults".to_string(), 8_800_000.0);
+    let spec = RateSpec::new(RateScale::PerTenThousand, Denominator::Covariate("adults".to_string()));
+    let records = apply_rate_spec(&[record], &spec);
+
+    let path = std::env::temp_dir().join("mia_test_spec.sqlite");
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_str().unwrap();
+    write_panel(&mut open_database(path).unwrap(), &records).unwrap();
+
+    let panel = load_panel(path).unwrap();
+    let ohio = panel.get("Ohio", 2010).unwrap();
+    assert_eq!(ohio.rate_spec, spec);
+    assert!((ohio.incarceration_rate - 50_000.0 / 880.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_non_finite_rates_are_stored_as_null() {
+    let mut records = vec![record(2013, None, Some(4_500))];
+    records[0].state_population = 0;
+    records[0].compute_rates();
+    assert!(!records[0].incarceration_rate.is_finite());
+
+    let path = std::env::temp_dir().join("mia_test_null_rates.sqlite");
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_str().unwrap();
+
+    let mut db = open_database(path).unwrap();
+    write_panel(&mut db, &records).unwrap();
+    write_outliers(&mut db, &records).unwrap();
+    write_regression(&mut db, "no variance", f32::NAN, f32::NAN, 1).unwrap();
+
+    for query in [
+        "SELECT incarceration_rate FROM panel",
+        "SELECT crime_rate FROM outliers",
+        "SELECT slope FROM regressions",
+    ] {
+        let value: Option<f64> = db.query_row(query, [], |r| r.get(0)).unwrap();
+        assert_eq!(value, None, "{}", query);
+    }
+
+    let panel = load_panel(path).unwrap();
+    assert_eq!(panel.get("Ohio", 2013).unwrap().prisoner_count, records[0].prisoner_count);
+}
@@ -0,0 +1,27 @@
+use mass_incarceration_analysis::data_processing::{filter_by_state, process_dataset};
+use mass_incarceration_analysis::states::{lookup_state, resolve_jurisdiction, Division, Region};
+
+#[test]
+fn test_every_spelling_resolves_to_one_state() {
+    for name in ["Arizona", "ARIZONA", "  arizona ", "AZ", "04", "4"] {
+        assert_eq!(resolve_jurisdiction(name).as_deref(), Some("ARIZONA"), "{}", name);
+    }
+    assert_eq!(resolve_jurisdiction("DC").as_deref(), Some("DISTRICT OF COLUMBIA"));
+    assert_eq!(resolve_jurisdiction("Federal").as_deref(), Some("FEDERAL"));
+    assert_eq!(resolve_jurisdiction("Atlantis"), None);
+
+    let massachusetts = lookup_state("Massachusetts").unwrap();
+    assert_eq!(massachusetts.postal, "MA");
+    assert_eq!(massachusetts.fips, 25);
+    assert_eq!(massachusetts.division, Some(Division::NewEngland));
+    assert_eq!(massachusetts.region(), Some(Region::Northeast));
+}
+
+#[test]
+fn test_filter_by_state_resolves_names() {
+    let (records, _, _) = process_dataset("crime_and_incarceration_by_state.csv").unwrap();
+    assert_eq!(filter_by_state(&records, "AZ").unwrap().len(), filter_by_state(&records, "Arizona").unwrap().len());
+    assert!(filter_by_state(&records, "Atlantis").is_err());
+    // Known state without rows (FEDERAL is not part of the cleaned panel)
+    assert!(filter_by_state(&records, "Federal").unwrap().is_empty());
+}
@@ -0,0 +1,47 @@
+use mass_incarceration_analysis::data_processing::{clean_record, validate_record, DirtyRecord};
+use mass_incarceration_analysis::validation::{IssueReason, Severity};
+
+fn dirty(prisoner_count: &str, state_population: &str, robbery: &str) -> DirtyRecord {
+    DirtyRecord {
+        jurisdiction: "ALABAMA".to_string(),
+        year: "2001".to_string(),
+        prisoner_count: prisoner_count.to_string(),
+        state_population: state_population.to_string(),
+        violent_crime_total: "19582.0".to_string(),
+        robbery: robbery.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_missing_population_rejects_row() {
+    let issues = clean_record(dirty("24741", "", "5584.0")).unwrap_err();
+    let population = issues.iter().find(|i| i.field == "state_population").unwrap();
+    assert_eq!(population.reason, IssueReason::Missing);
+    assert_eq!(population.severity, Severity::Error);
+}
+
+#[test]
+fn test_bad_subcount_is_a_warning() {
+    let record = dirty("24,741", "4468912.0", "n/a");
+    let issues = validate_record(&record);
+
+    let robbery = issues.iter().find(|i| i.field == "robbery").unwrap();
+    assert_eq!(robbery.reason, IssueReason::Unparseable);
+    assert_eq!(robbery.severity, Severity::Warning);
+    assert_eq!(robbery.raw_value, "n/a");
+
+    // Warnings keep the row, with the bad value coerced to 0
+    let clean = clean_record(record).unwrap();
+    assert_eq!(clean.prisoner_count, 24741);
+    assert_eq!(clean.robbery, 0);
+    assert!(clean.validation_warnings.contains(&"robbery".to_string()));
+    assert!(!clean.validation_warnings.contains(&"prisoner_count".to_string()));
+}
+
+#[test]
+fn test_negative_and_overflow_counts() {
+    let issues = validate_record(&dirty("-5", "99999999999", "5584.0"));
+    assert!(issues.iter().any(|i| i.field == "prisoner_count" && i.reason == IssueReason::Negative));
+    assert!(issues.iter().any(|i| i.field == "state_population" && i.reason == IssueReason::Overflow));
+}